@@ -1,9 +1,22 @@
 use std::string::ToString;
-use nannou::rand::random_range;
+use rand_chacha::ChaCha12Rng;
+use roughr::Srgba;
 
-pub fn get_random_palette() -> [String; 5]  {
+use crate::carbon::carbon_random;
+
+pub fn get_random_palette(rng: &mut ChaCha12Rng) -> [String; 5]  {
     let colors = color_set();
-    return colors[random_range(0, colors.len())].clone();
+    return colors[carbon_random::pick_index(rng, colors.len())].clone();
+}
+
+// position of a palette in the built in set, None for palettes from elsewhere
+pub fn palette_index(palette: &[String; 5]) -> Option<usize> {
+    color_set().iter().position(|p| p == palette)
+}
+
+pub fn parse_colour(colour: &str) -> Srgba {
+    let c = colour.parse::<csscolorparser::Color>().unwrap();
+    Srgba::new(c.r as f32, c.g as f32, c.b as f32, c.a as f32)
 }
 
 
@@ -0,0 +1,179 @@
+use std::collections::HashMap;
+
+use nannou::prelude::*;
+
+// Small polygon helpers for the tiled layouts. Polygons are plain point lists,
+// counter clockwise, without the first point repeated at the end.
+
+pub fn signed_area(poly: &[Point2]) -> f32 {
+    let mut area = 0.0;
+    for i in 0..poly.len() {
+        let a = poly[i];
+        let b = poly[(i + 1) % poly.len()];
+        area += a.x * b.y - b.x * a.y;
+    }
+    area / 2.0
+}
+
+pub fn make_ccw(poly: &mut [Point2]) {
+    if signed_area(poly) < 0.0 {
+        poly.reverse();
+    }
+}
+
+pub fn centroid(poly: &[Point2]) -> Point2 {
+    if poly.is_empty() {
+        return pt2(0.0, 0.0);
+    }
+    let sum = poly.iter().fold(pt2(0.0, 0.0), |acc, p| acc + *p);
+    sum / poly.len() as f32
+}
+
+// centre of mass of the filled polygon, falls back to the point average
+// when the area is zero
+pub fn area_centroid(poly: &[Point2]) -> Point2 {
+    let area = signed_area(poly);
+    if area.abs() < 0.0001 {
+        return centroid(poly);
+    }
+
+    let mut c = pt2(0.0, 0.0);
+    for i in 0..poly.len() {
+        let a = poly[i];
+        let b = poly[(i + 1) % poly.len()];
+        let cross = a.x * b.y - b.x * a.y;
+        c += (a + b) * cross;
+    }
+    c / (6.0 * area)
+}
+
+pub fn bounding_rect(poly: &[Point2]) -> Rect {
+    let mut min = pt2(f32::MAX, f32::MAX);
+    let mut max = pt2(f32::MIN, f32::MIN);
+    for p in poly.iter() {
+        min = min.min(*p);
+        max = max.max(*p);
+    }
+    Rect::from_corners(min, max)
+}
+
+// closest distance from `p` to any edge
+pub fn edge_distance(poly: &[Point2], p: Point2) -> f32 {
+    let mut best = f32::MAX;
+    for i in 0..poly.len() {
+        let a = poly[i];
+        let b = poly[(i + 1) % poly.len()];
+        let ab = b - a;
+        let t = if ab.length_squared() > 0.0 {
+            ((p - a).dot(ab) / ab.length_squared()).clamp(0.0, 1.0)
+        } else {
+            0.0
+        };
+        best = best.min(p.distance(a + ab * t));
+    }
+    best
+}
+
+// square around the circle that sits inside a convex cell, shapes get
+// fitted to this
+pub fn fit_rect(poly: &[Point2]) -> Rect {
+    let c = centroid(poly);
+    let r = edge_distance(poly, c);
+    Rect::from_xy_wh(c, pt2(r * 2.0, r * 2.0))
+}
+
+// move every edge `amount` towards the inside (mitred corners). Fine for the
+// small gaps we use, large amounts on thin cells will fold over.
+pub fn inset(poly: &[Point2], amount: f32) -> Vec<Point2> {
+    let n = poly.len();
+    if n < 3 || amount == 0.0 {
+        return poly.to_vec();
+    }
+
+    // left hand normal of each edge, the inside of a ccw polygon
+    let normals: Vec<Point2> = (0..n)
+        .map(|i| {
+            let d = poly[(i + 1) % n] - poly[i];
+            let len = d.length();
+            if len > 0.0 { pt2(-d.y, d.x) / len } else { pt2(0.0, 0.0) }
+        })
+        .collect();
+
+    (0..n)
+        .map(|i| {
+            let n1 = normals[(i + n - 1) % n];
+            let n2 = normals[i];
+            let denom = 1.0 + n1.dot(n2);
+            if denom < 0.000001 {
+                return poly[i] + n2 * amount;
+            }
+            poly[i] + (n1 + n2) * (amount / denom)
+        })
+        .collect()
+}
+
+// Outline of a group of tiles that share whole edges. Edges used by two tiles
+// cancel out and what is left is chained back into a loop. None when the
+// group is not one simple loop (touching at a corner only, or with a hole).
+pub fn merge_outline(polys: &[Vec<Point2>]) -> Option<Vec<Point2>> {
+    let mut edges: Vec<(Point2, Point2)> = vec![];
+    for poly in polys.iter() {
+        let mut poly = poly.clone();
+        make_ccw(&mut poly);
+        for i in 0..poly.len() {
+            edges.push((poly[i], poly[(i + 1) % poly.len()]));
+        }
+    }
+
+    let mut counts: HashMap<(PointKey, PointKey), u32> = HashMap::new();
+    for (a, b) in edges.iter() {
+        *counts.entry((point_key(*a), point_key(*b))).or_insert(0) += 1;
+    }
+
+    let outer: Vec<(Point2, Point2)> = edges.into_iter()
+        .filter(|(a, b)| !counts.contains_key(&(point_key(*b), point_key(*a))))
+        .collect();
+
+    if outer.is_empty() {
+        return None;
+    }
+
+    let mut next: HashMap<PointKey, usize> = HashMap::new();
+    for (i, (a, _)) in outer.iter().enumerate() {
+        if next.insert(point_key(*a), i).is_some() {
+            return None;
+        }
+    }
+
+    let mut outline = vec![];
+    let mut i = 0;
+    for _ in 0..outer.len() {
+        outline.push(outer[i].0);
+        i = *next.get(&point_key(outer[i].1))?;
+        if i == 0 {
+            break;
+        }
+    }
+
+    if outline.len() != outer.len() {
+        return None;
+    }
+
+    Some(outline)
+}
+
+// true when the two polygons have an edge in common
+pub fn shares_edge(a: &[Point2], b: &[Point2]) -> bool {
+    let keys: Vec<PointKey> = b.iter().map(|p| point_key(*p)).collect();
+    let shared = a.iter()
+        .filter(|p| keys.contains(&point_key(**p)))
+        .count();
+    shared >= 2
+}
+
+// tiles are built with float maths, so match corners on a 0.01mm grid
+type PointKey = (i64, i64);
+
+fn point_key(p: Point2) -> PointKey {
+    ((p.x * 100.0).round() as i64, (p.y * 100.0).round() as i64)
+}
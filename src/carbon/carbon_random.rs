@@ -0,0 +1,92 @@
+use rand::{Rng, SeedableRng};
+use rand_chacha::ChaCha12Rng;
+
+// Every random decision in the sketch is drawn from a stream derived from the
+// master seed, so the same seed + settings always gives the same artwork.
+// ChaCha12 is named instead of rand's StdRng, whose algorithm may change
+// between rand versions.
+#[derive(Clone, Copy)]
+pub enum RandomStream {
+    Layout,
+    Shape,
+    Transform,
+    Palette,
+    Colour,
+    Stroke,
+    FillStyle,
+    Roughr,
+}
+
+impl RandomStream {
+    fn name(&self) -> &'static str {
+        match self {
+            RandomStream::Layout => "layout",
+            RandomStream::Shape => "shape",
+            RandomStream::Transform => "transform",
+            RandomStream::Palette => "palette",
+            RandomStream::Colour => "colour",
+            RandomStream::Stroke => "stroke",
+            RandomStream::FillStyle => "fill_style",
+            RandomStream::Roughr => "roughr",
+        }
+    }
+}
+
+pub fn stream_rng(master_seed: u64, stream: RandomStream) -> ChaCha12Rng {
+    let salt = fnv1a(stream.name().as_bytes());
+    ChaCha12Rng::seed_from_u64(splitmix64(master_seed ^ salt))
+}
+
+pub fn random_seed() -> u64 {
+    rand::random::<u64>()
+}
+
+// numbers are used as-is, anything else (words, hex hashes...) gets hashed
+pub fn seed_from_str(text: &str) -> u64 {
+    let trimmed = text.trim();
+    match trimmed.parse::<u64>() {
+        Ok(seed) => seed,
+        Err(_) => fnv1a(trimmed.as_bytes()),
+    }
+}
+
+// usize is 32 bit on wasm, so sample as u32 to keep native + web in step
+pub fn pick_index(rng: &mut ChaCha12Rng, len: usize) -> usize {
+    rng.gen_range(0..len as u32) as usize
+}
+
+// index of the picked weight, None when every weight is zero
+pub fn pick_weighted(rng: &mut ChaCha12Rng, weights: &[f32]) -> Option<usize> {
+    let total: f32 = weights.iter().map(|w| w.max(0.0)).sum();
+    if total <= 0.0 {
+        return None;
+    }
+
+    let mut target = rng.gen::<f32>() * total;
+    for (i, w) in weights.iter().enumerate() {
+        let w = w.max(0.0);
+        if w > 0.0 && target < w {
+            return Some(i);
+        }
+        target -= w;
+    }
+
+    // float rounding, fall back to the last usable weight
+    weights.iter().rposition(|w| *w > 0.0)
+}
+
+fn fnv1a(bytes: &[u8]) -> u64 {
+    let mut hash: u64 = 0xcbf2_9ce4_8422_2325;
+    for b in bytes {
+        hash ^= *b as u64;
+        hash = hash.wrapping_mul(0x0000_0100_0000_01b3);
+    }
+    hash
+}
+
+fn splitmix64(seed: u64) -> u64 {
+    let mut z = seed.wrapping_add(0x9e37_79b9_7f4a_7c15);
+    z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+    z ^ (z >> 31)
+}
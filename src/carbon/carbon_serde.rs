@@ -0,0 +1,99 @@
+// serde `with` helpers for the nannou / roughr types that can't derive it.
+// Kept small and readable so layout files can be written by hand:
+// rects are {x, y, w, h}, points [x, y], colours [r, g, b, a] (0 - 1).
+
+pub mod rect {
+    use nannou::geom::Rect;
+    use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+    #[derive(Serialize, Deserialize)]
+    struct RectData {
+        x: f32,
+        y: f32,
+        w: f32,
+        h: f32,
+    }
+
+    pub fn serialize<S: Serializer>(r: &Rect, s: S) -> Result<S::Ok, S::Error> {
+        RectData { x: r.x(), y: r.y(), w: r.w(), h: r.h() }.serialize(s)
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(d: D) -> Result<Rect, D::Error> {
+        let r = RectData::deserialize(d)?;
+        Ok(Rect::from_x_y_w_h(r.x, r.y, r.w, r.h))
+    }
+}
+
+pub mod points {
+    use nannou::geom::{pt2, Point2};
+    use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+    pub fn serialize<S: Serializer>(points: &Option<Vec<Point2>>, s: S) -> Result<S::Ok, S::Error> {
+        points.as_ref()
+            .map(|points| points.iter().map(|p| [p.x, p.y]).collect::<Vec<_>>())
+            .serialize(s)
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(d: D) -> Result<Option<Vec<Point2>>, D::Error> {
+        let points = Option::<Vec<[f32; 2]>>::deserialize(d)?;
+        Ok(points.map(|points| points.iter().map(|[x, y]| pt2(*x, *y)).collect()))
+    }
+}
+
+pub mod colour {
+    use roughr::Srgba;
+    use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+    pub fn serialize<S: Serializer>(c: &Srgba, s: S) -> Result<S::Ok, S::Error> {
+        [c.red, c.green, c.blue, c.alpha].serialize(s)
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(d: D) -> Result<Srgba, D::Error> {
+        let [r, g, b, a] = <[f32; 4]>::deserialize(d)?;
+        Ok(Srgba::new(r, g, b, a))
+    }
+}
+
+pub mod option_colour {
+    use roughr::Srgba;
+    use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+    pub fn serialize<S: Serializer>(c: &Option<Srgba>, s: S) -> Result<S::Ok, S::Error> {
+        c.map(|c| [c.red, c.green, c.blue, c.alpha]).serialize(s)
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(d: D) -> Result<Option<Srgba>, D::Error> {
+        let c = Option::<[f32; 4]>::deserialize(d)?;
+        Ok(c.map(|[r, g, b, a]| Srgba::new(r, g, b, a)))
+    }
+}
+
+// roughr fill styles by name, null for no fill
+pub mod fill_style {
+    use roughr::core::FillStyle;
+    use serde::de::Error;
+    use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+    pub fn serialize<S: Serializer>(style: &Option<FillStyle>, s: S) -> Result<S::Ok, S::Error> {
+        style.as_ref().map(|style| format!("{:?}", style)).serialize(s)
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(d: D) -> Result<Option<FillStyle>, D::Error> {
+        let name = match Option::<String>::deserialize(d)? {
+            Some(name) => name,
+            None => return Ok(None),
+        };
+
+        let style = match name.as_str() {
+            "Solid" => FillStyle::Solid,
+            "Hachure" => FillStyle::Hachure,
+            "ZigZag" => FillStyle::ZigZag,
+            "CrossHatch" => FillStyle::CrossHatch,
+            "Dots" => FillStyle::Dots,
+            "Dashed" => FillStyle::Dashed,
+            "ZigZagLine" => FillStyle::ZigZagLine,
+            _ => return Err(D::Error::custom(format!("unknown fill style {}", name))),
+        };
+        Ok(Some(style))
+    }
+}
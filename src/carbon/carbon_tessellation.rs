@@ -0,0 +1,138 @@
+use nannou::prelude::*;
+
+use crate::carbon::carbon_polygon;
+
+// Voronoi cells and Delaunay triangles for a handful of sites (a few hundred
+// at most), so the simple O(n^2) versions are plenty.
+
+// one ccw cell per site, clipped to `bounds`
+pub fn voronoi_cells(sites: &[Point2], bounds: Rect) -> Vec<Vec<Point2>> {
+    sites.iter()
+        .enumerate()
+        .map(|(i, site)| {
+            let mut cell = vec![
+                bounds.bottom_left(),
+                bounds.bottom_right(),
+                bounds.top_right(),
+                bounds.top_left(),
+            ];
+
+            for (j, other) in sites.iter().enumerate() {
+                if i == j || site.distance(*other) < 0.0001 {
+                    continue;
+                }
+                // keep the half of the plane closer to `site`
+                let mid = (*site + *other) / 2.0;
+                cell = clip_half_plane(&cell, mid, *other - *site);
+                if cell.is_empty() {
+                    break;
+                }
+            }
+
+            cell
+        })
+        .collect()
+}
+
+// move every site to the middle of its cell, `iterations` times
+pub fn lloyd_relax(sites: &[Point2], bounds: Rect, iterations: u32) -> Vec<Point2> {
+    let mut sites = sites.to_vec();
+
+    for _ in 0..iterations {
+        sites = voronoi_cells(&sites, bounds)
+            .iter()
+            .zip(sites.iter())
+            .map(|(cell, site)| {
+                if cell.len() < 3 {
+                    *site
+                } else {
+                    carbon_polygon::area_centroid(cell)
+                }
+            })
+            .collect();
+    }
+
+    sites
+}
+
+// bowyer watson, indices into `points`, each triangle ccw
+pub fn delaunay_triangles(points: &[Point2]) -> Vec<[usize; 3]> {
+    if points.len() < 3 {
+        return vec![];
+    }
+
+    // one triangle around everything, removed again at the end
+    let bounds = carbon_polygon::bounding_rect(points);
+    let size = bounds.w().max(bounds.h()).max(1.0) * 20.0;
+    let c = bounds.xy();
+
+    let mut pts = points.to_vec();
+    let n = pts.len();
+    pts.push(pt2(c.x - size, c.y - size));
+    pts.push(pt2(c.x + size, c.y - size));
+    pts.push(pt2(c.x, c.y + size));
+
+    let mut triangles: Vec<[usize; 3]> = vec![[n, n + 1, n + 2]];
+
+    for i in 0..n {
+        let p = pts[i];
+
+        let (bad, good): (Vec<[usize; 3]>, Vec<[usize; 3]>) = triangles.into_iter()
+            .partition(|t| in_circumcircle(&pts, t, p));
+        triangles = good;
+
+        // edges of the hole that only one bad triangle uses
+        let edges: Vec<(usize, usize)> = bad.iter()
+            .flat_map(|t| [(t[0], t[1]), (t[1], t[2]), (t[2], t[0])])
+            .collect();
+
+        for (a, b) in edges.iter() {
+            let shared = edges.iter()
+                .filter(|(x, y)| (x == a && y == b) || (x == b && y == a))
+                .count();
+            if shared == 1 {
+                triangles.push(ccw_triangle(&pts, [*a, *b, i]));
+            }
+        }
+    }
+
+    triangles.into_iter()
+        .filter(|t| t.iter().all(|v| *v < n))
+        .collect()
+}
+
+fn in_circumcircle(pts: &[Point2], t: &[usize; 3], p: Point2) -> bool {
+    let (a, b, c) = (pts[t[0]] - p, pts[t[1]] - p, pts[t[2]] - p);
+
+    let det = (a.x * a.x + a.y * a.y) * (b.x * c.y - c.x * b.y)
+        - (b.x * b.x + b.y * b.y) * (a.x * c.y - c.x * a.y)
+        + (c.x * c.x + c.y * c.y) * (a.x * b.y - b.x * a.y);
+
+    det > 0.0
+}
+
+fn ccw_triangle(pts: &[Point2], t: [usize; 3]) -> [usize; 3] {
+    let area = carbon_polygon::signed_area(&[pts[t[0]], pts[t[1]], pts[t[2]]]);
+    if area < 0.0 { [t[0], t[2], t[1]] } else { t }
+}
+
+// sutherland hodgman against one edge, keeps points where (p - origin) . normal <= 0
+fn clip_half_plane(poly: &[Point2], origin: Point2, normal: Point2) -> Vec<Point2> {
+    let side = |p: Point2| (p - origin).dot(normal);
+    let mut out = vec![];
+
+    for i in 0..poly.len() {
+        let a = poly[i];
+        let b = poly[(i + 1) % poly.len()];
+        let (sa, sb) = (side(a), side(b));
+
+        if sa <= 0.0 {
+            out.push(a);
+        }
+        if (sa < 0.0 && sb > 0.0) || (sa > 0.0 && sb < 0.0) {
+            out.push(a + (b - a) * (sa / (sa - sb)));
+        }
+    }
+
+    out
+}
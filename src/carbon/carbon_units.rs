@@ -0,0 +1,13 @@
+// Physical sizes. Layouts are set out in millimetres, the scene / svg use css
+// pixels (96 per inch) and raster exports use the page dpi.
+
+pub const MM_PER_INCH: f32 = 25.4;
+pub const CSS_DPI: f32 = 96.0;
+
+pub fn mm_to_px(mm: f32, dpi: f32) -> f32 {
+    mm / MM_PER_INCH * dpi
+}
+
+pub fn px_to_mm(px: f32, dpi: f32) -> f32 {
+    px / dpi * MM_PER_INCH
+}
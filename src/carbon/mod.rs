@@ -1,3 +1,8 @@
 pub mod carbon_utils;
 pub mod carbon_colors;
-pub mod carbon_sketch_helpers;
\ No newline at end of file
+pub mod carbon_sketch_helpers;
+pub mod carbon_random;
+pub mod carbon_polygon;
+pub mod carbon_tessellation;
+pub mod carbon_units;
+pub mod carbon_serde;
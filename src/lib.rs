@@ -4,8 +4,19 @@ use wasm_bindgen::prelude::wasm_bindgen;
 use sketch::{run_app};
 
 mod sketch;
-mod carbon;
-mod sketch_model;
+pub mod carbon;
+pub mod sketch_model;
+pub mod sketch_layout;
+pub mod sketch_scene;
+pub mod sketch_svg;
+pub mod sketch_json;
+pub mod sketch_presets;
+pub mod sketch_history;
+pub mod sketch_traits;
+#[cfg(not(target_arch = "wasm32"))]
+pub mod sketch_png;
+#[cfg(not(target_arch = "wasm32"))]
+pub mod sketch_edition;
 
 // web app entry_point
 #[wasm_bindgen]
@@ -13,7 +24,8 @@ pub async fn main_web() {
     #[cfg(debug_assertions)]
     console_error_panic_hook::set_once();
 
-    let model = sketch_model::Model::new();
+    let mut model = sketch_model::Model::new();
+    sketch_presets::apply_startup_preset(&mut model);
 
     block_on(async {
         run_app(model).await;
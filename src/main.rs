@@ -8,12 +8,206 @@ use sketch::{run_app};
 mod sketch;
 mod carbon;
 mod sketch_model;
+mod sketch_layout;
+mod sketch_scene;
+mod sketch_svg;
+mod sketch_json;
+mod sketch_presets;
+mod sketch_history;
+mod sketch_png;
+mod sketch_edition;
+mod sketch_traits;
 
 fn main() {
 
-    let model = sketch_model::Model::new();
+    let args: Vec<String> = std::env::args().collect();
+
+    let svg_path = arg_value(&args, "--export-svg");
+    let json_path = arg_value(&args, "--export-json");
+    let edition_run = args.get(1).map(|a| a.as_str()) == Some("edition");
+    let headless = edition_run || svg_path.is_some() || json_path.is_some();
+
+    let mut model = sketch_model::Model::new();
+    sketch_presets::apply_startup_preset(&mut model);
+
+    // the panel shows a broken startup preset too, an export has no panel
+    if let Some(e) = &model.last_error {
+        eprintln!("{}", e);
+        if headless {
+            std::process::exit(1);
+        }
+    }
+
+    if let Some(name) = arg_value(&args, "--preset") {
+        match sketch_presets::PresetStore::default_location().load(name) {
+            Ok(settings) => {
+                model.settings = settings;
+                model.selected_preset = name.to_string();
+            }
+            Err(e) => {
+                eprintln!("{}", e);
+                std::process::exit(1);
+            }
+        }
+    }
+
+    if let Some(seed) = arg_value(&args, "--seed") {
+        model.settings.seed = carbon::carbon_random::seed_from_str(seed);
+        model.settings.seed_text = seed.to_string();
+    }
+
+    if let Some(layout) = arg_value(&args, "--layout") {
+        if sketch_layout::find_strategy(layout).is_none() {
+            eprintln!(
+                "unknown layout {}, pick one of: {}",
+                layout,
+                sketch_layout::strategy_names().join(", "),
+            );
+            return;
+        }
+        model.settings.layout = layout.to_string();
+    }
+
+    if let Some(page) = arg_value(&args, "--page") {
+        match sketch_model::PageSize::all().iter().find(|s| s.label().eq_ignore_ascii_case(page)) {
+            Some(size) => model.settings.page.size = *size,
+            None => {
+                let labels: Vec<&str> = sketch_model::PageSize::all().iter().map(|s| s.label()).collect();
+                eprintln!("unknown page {}, pick one of: {}", page, labels.join(", "));
+                return;
+            }
+        }
+    }
+
+    if args.iter().any(|a| a == "--landscape") {
+        model.settings.page.orientation = sketch_model::Orientation::Landscape;
+    }
+
+    // nn_001_compat edition --seeds 1..=50 [--format svg|png|both] [--out dir] [--prefix name]
+    // the other flags (--preset, --layout, --page ...) set up the shared settings
+    if edition_run {
+        edition(&args, &model.settings);
+        return;
+    }
+
+    // nn_001_compat report --seeds 1..=500 [--format csv|md|both] [--out dir] [--prefix name]
+    if args.get(1).map(|a| a.as_str()) == Some("report") {
+        report(&args, &model.settings);
+        return;
+    }
+
+    // nn_001_compat --load piece.json : draw a saved piece instead of a new one
+    if let Some(file_path) = arg_value(&args, "--load") {
+        match sketch_json::load_json(file_path) {
+            Ok(composition) => {
+                sketch::apply_composition(&mut model, composition);
+                model.composition_loaded = true;
+            }
+            Err(e) => {
+                eprintln!("{}", e);
+                return;
+            }
+        }
+    }
+
+    // nn_001_compat --export-svg out.svg / --export-json out.json : write the
+    // piece and exit without a window
+    if svg_path.is_some() || json_path.is_some() {
+        if !model.composition_loaded {
+            sketch::generate(&mut model);
+        }
+
+        if let Some(file_path) = svg_path {
+            match sketch_svg::save_svg(model.scene.as_ref().unwrap(), file_path) {
+                Ok(_) => println!("saved {} (seed {})", file_path, model.settings.seed),
+                Err(e) => eprintln!("could not save {}: {}", file_path, e),
+            }
+        }
+
+        if let Some(file_path) = json_path {
+            let text = sketch_json::composition_to_json(
+                &model.settings,
+                model.raw_palette.as_ref().unwrap(),
+                model.layout.as_ref().unwrap(),
+            );
+            match sketch_json::save_json(&text, file_path) {
+                Ok(_) => println!("saved {} (seed {})", file_path, model.settings.seed),
+                Err(e) => eprintln!("could not save {}: {}", file_path, e),
+            }
+        }
+        return;
+    }
 
     block_on(async {
         run_app(model).await;
     });
 }
+
+fn edition(args: &[String], settings: &sketch_model::Settings) {
+    let seeds = match seeds_arg(args, "edition") {
+        Some(seeds) => seeds,
+        None => return,
+    };
+
+    let format_name = arg_value(args, "--format").unwrap_or("svg");
+    let format = match sketch_edition::EditionFormat::from_name(format_name) {
+        Some(format) => format,
+        None => {
+            eprintln!("unknown format {}, pick one of: svg, png, both", format_name);
+            return;
+        }
+    };
+
+    let out_dir = arg_value(args, "--out").unwrap_or("edition");
+    let prefix = arg_value(args, "--prefix").unwrap_or("nn_001");
+
+    match sketch_edition::run_edition(settings, &seeds, format, out_dir, prefix) {
+        Ok(files) => println!("edition of {} saved to {} ({} files)", seeds.len(), out_dir, files.len()),
+        Err(e) => eprintln!("{}", e),
+    }
+}
+
+fn report(args: &[String], settings: &sketch_model::Settings) {
+    let seeds = match seeds_arg(args, "report") {
+        Some(seeds) => seeds,
+        None => return,
+    };
+
+    let format_name = arg_value(args, "--format").unwrap_or("both");
+    let format = match sketch_edition::ReportFormat::from_name(format_name) {
+        Some(format) => format,
+        None => {
+            eprintln!("unknown format {}, pick one of: csv, md, both", format_name);
+            return;
+        }
+    };
+
+    let out_dir = arg_value(args, "--out").unwrap_or("report");
+    let prefix = arg_value(args, "--prefix").unwrap_or("nn_001");
+
+    match sketch_edition::run_report(settings, &seeds, format, out_dir, prefix) {
+        Ok(traits) => println!("traits of {} seeds saved to {}", traits.len(), out_dir),
+        Err(e) => eprintln!("{}", e),
+    }
+}
+
+fn seeds_arg(args: &[String], command: &str) -> Option<Vec<String>> {
+    match arg_value(args, "--seeds").map(sketch_edition::parse_seeds) {
+        Some(Ok(seeds)) => Some(seeds),
+        Some(Err(e)) => {
+            eprintln!("{}", e);
+            None
+        }
+        None => {
+            eprintln!("{} needs --seeds, a range like 1..=50 or a list like 7,42,sunrise", command);
+            None
+        }
+    }
+}
+
+fn arg_value<'a>(args: &'a [String], flag: &str) -> Option<&'a str> {
+    args.iter()
+        .position(|a| a == flag)
+        .and_then(|i| args.get(i + 1))
+        .map(|s| s.as_str())
+}
@@ -7,26 +7,246 @@ use nannou::wgpu::{Backends, BufferView, DeviceDescriptor, Limits};
 use nannou_egui::{Egui, egui};
 use nannou_egui::egui::Shape;
 use palette::IntoColor;
-use points_on_curve::points_on_bezier_curves;
-use rand::rngs::StdRng;
-use roughr::core::{Drawable, OpSetType, OpType, OptionsBuilder, FillStyle};
-use roughr::generator::Generator;
+use rand::Rng;
+use roughr::core::Options;
 use roughr::Srgba;
-use roughr::Point2D;
 
-use crate::sketch_model::{HigResWorker, LayoutItem, Model, Shapes};
+use crate::sketch_model::{HigResWorker, ItemStyle, ItemTransform, LayoutItem, Model, Orientation, PageSize, RotationMode, Settings, Shapes, StrokeMode};
+use crate::sketch_layout::{self, GridLayout, LayoutStrategy};
+use crate::sketch_scene::{self, Scene};
+use crate::sketch_svg;
+use crate::sketch_json::{self, Composition};
+use crate::sketch_presets::PresetStore;
+use crate::sketch_history::Snapshot;
 use crate::carbon;
 use carbon::carbon_sketch_helpers;
+use carbon::carbon_random::{self, RandomStream};
+use carbon::carbon_units;
 
+// size of the preview window, the page is scaled to fit inside it
 const DESIGN_WIDTH: i32 = 900 / 2;
 const DESIGN_HEIGHT: i32 = 1200 / 2;
 
-pub struct Settings {
-    show_grid: bool,
+
+// builds the whole piece from the settings, no window needed
+pub fn generate(model: &mut Model) {
+    let mut palette_rng = carbon_random::stream_rng(
+        model.settings.seed,
+        RandomStream::Palette,
+    );
+    model.raw_palette = Some(
+        carbon::carbon_colors::get_random_palette(&mut palette_rng)
+    );
+
+    let mut shape_rng = carbon_random::stream_rng(
+        model.settings.seed,
+        RandomStream::Shape,
+    );
+    let mut layout_rng = carbon_random::stream_rng(
+        model.settings.seed,
+        RandomStream::Layout,
+    );
+
+    // unknown names (old presets, typos on the command line) fall back to the grid
+    let strategy: Box<dyn LayoutStrategy> = sketch_layout::find_strategy(&model.settings.layout)
+        .unwrap_or_else(|| Box::new(GridLayout));
+
+    // layouts are set out in millimetres, everything after is in css pixels
+    let mut layout = strategy.generate(
+        model.settings.page.rect_mm(),
+        &model.settings,
+        &mut shape_rng,
+        &mut layout_rng,
+    );
+    scale_layout(&mut layout, carbon_units::mm_to_px(1.0, carbon_units::CSS_DPI));
+
+    let page = model.settings.page.rect_px();
+
+    orient_layout(&mut layout, page, &model.settings);
+
+    style_layout(
+        &mut layout,
+        model.raw_palette.as_ref().unwrap(),
+        &model.settings,
+    );
+
+    model.scene = Some(
+        sketch_scene::build_scene(&layout, page.w(), page.h())
+    );
+    model.layout = Some(layout);
+}
+
+
+// draws a saved (or hand written) piece instead of generating one
+pub fn apply_composition(model: &mut Model, composition: Composition) {
+    if let Some(settings) = composition.settings {
+        model.settings = settings;
+    }
+
+    match composition.palette {
+        Some(palette) => model.raw_palette = Some(palette),
+        None => {
+            let mut palette_rng = carbon_random::stream_rng(
+                model.settings.seed,
+                RandomStream::Palette,
+            );
+            model.raw_palette = Some(
+                carbon::carbon_colors::get_random_palette(&mut palette_rng)
+            );
+        }
+    }
+
+    let mut layout = composition.layout;
+
+    style_layout(
+        &mut layout,
+        model.raw_palette.as_ref().unwrap(),
+        &model.settings,
+    );
+
+    let page = model.settings.page.rect_px();
+    model.scene = Some(
+        sketch_scene::build_scene(&layout, page.w(), page.h())
+    );
+    model.layout = Some(layout);
+}
+
+
+enum PresetAction {
+    Load,
+    Save,
+    Rename,
+    Delete,
+}
+
+// save uses the name field (or the selected preset), rename moves the
+// selected preset to the name field
+fn run_preset_action(model: &mut Model, action: PresetAction) {
+    let store = PresetStore::default_location();
+    let selected = model.selected_preset.clone();
+    let typed = model.preset_name_input.trim().to_string();
+
+    let result = match action {
+        PresetAction::Load => store.load(&selected).map(|settings| {
+            model.settings = settings;
+            model.is_setup = false;
+        }),
+        PresetAction::Save => {
+            let name = if typed.is_empty() { selected } else { typed };
+            store.save(&name, &model.settings).map(|_| {
+                model.selected_preset = name;
+            })
+        }
+        PresetAction::Rename => store.rename(&selected, &typed).map(|_| {
+            model.selected_preset = typed;
+        }),
+        PresetAction::Delete => store.delete(&selected).map(|_| {
+            model.selected_preset.clear();
+        }),
+    };
+
+    model.last_error = result.err();
+    model.preset_names = store.names();
+}
+
+
+fn svg_file_name(model: &Model) -> String {
+    format!("nn_001_{}.svg", model.settings.seed)
+}
+
+fn json_file_name(model: &Model) -> String {
+    format!("nn_001_{}.json", model.settings.seed)
+}
+
+fn export_composition(model: &Model) {
+    if let (Some(palette), Some(layout)) = (model.raw_palette.as_ref(), model.layout.as_ref()) {
+        let text = sketch_json::composition_to_json(&model.settings, palette, layout);
+        sketch_json::export_json(&text, &json_file_name(model));
+    }
+}
+
+// a path on native, the json itself in the browser (no file access there)
+fn load_composition(model: &mut Model) {
+    #[cfg(not(target_arch = "wasm32"))]
+    let composition = sketch_json::load_json(model.json_input.trim());
+    #[cfg(target_arch = "wasm32")]
+    let composition = sketch_json::composition_from_json(&model.json_input);
+
+    match composition {
+        Ok(composition) => {
+            model.last_error = None;
+            apply_composition(model, composition);
+            record_history(model);
+        }
+        Err(e) => model.last_error = Some(e),
+    }
+}
+
+
+// history -------------------------------------------------------------------
+enum HistoryMove {
+    Undo,
+    Redo,
+    Jump(usize),
+}
+
+fn pointer_down(model: &Model) -> bool {
+    match model.e_gui.as_ref() {
+        Some(egui) => egui.ctx().input().pointer.any_down(),
+        None => false,
+    }
+}
+
+fn record_history(model: &mut Model) {
+    let dragging = pointer_down(model);
+
+    if let (Some(palette), Some(layout)) = (model.raw_palette.as_ref(), model.layout.as_ref()) {
+        let snapshot = Snapshot {
+            label: format!(
+                "{}. {} / seed {}",
+                model.history.pushed() + 1,
+                model.settings.layout,
+                model.settings.seed_text,
+            ),
+            settings: model.settings.clone(),
+            palette: palette.clone(),
+            layout: layout.clone(),
+        };
+
+        if dragging && model.history_drag {
+            model.history.replace_current(snapshot);
+        } else {
+            model.history.push(snapshot);
+        }
+    }
+    model.history_drag = dragging;
+}
+
+// brings back an earlier piece as it was drawn, nothing is generated
+fn move_in_history(model: &mut Model, history_move: HistoryMove) {
+    let snapshot = match history_move {
+        HistoryMove::Undo => model.history.undo(),
+        HistoryMove::Redo => model.history.redo(),
+        HistoryMove::Jump(i) => model.history.jump(i),
+    };
+
+    if let Some(snapshot) = snapshot.cloned() {
+        apply_composition(model, Composition {
+            version: sketch_json::FORMAT_VERSION,
+            settings: Some(snapshot.settings),
+            palette: Some(snapshot.palette),
+            layout: snapshot.layout,
+        });
+    }
 }
 
 
 fn update(app: &App, model: &mut Model, update: Update) {
+    // letting go ends the drag, even when the last frame changed nothing
+    if !pointer_down(model) {
+        model.history_drag = false;
+    }
+
     if !model.is_setup {
         model.setup(
             app,
@@ -34,36 +254,215 @@ fn update(app: &App, model: &mut Model, update: Update) {
             DESIGN_WIDTH,
         );
 
-        model.layout = Some(
-            generate_layout(
-                app.window_rect(),
-                model.settings.page_padding,
-                model.settings.row_total,
-                model.settings.col_total,
-                model.settings.gap,
-            )
-        );
+        // a loaded piece is shown as it is, the next change regenerates
+        if model.composition_loaded {
+            model.composition_loaded = false;
+        } else {
+            generate(model);
+        }
+        record_history(model);
     }
 
     if model.e_gui.is_some() {
         let egui = &mut model.e_gui.as_mut().unwrap();
         let setttings = &mut model.settings;
 
+        let mut export_requested = false;
+        let mut save_json_requested = false;
+        let mut load_json_requested = false;
+        let json_input = &mut model.json_input;
+        let mut preset_action = None;
+        let preset_names = &model.preset_names;
+        let selected_preset = &mut model.selected_preset;
+        let preset_name_input = &mut model.preset_name_input;
+        let last_error = &model.last_error;
+        let mut history_move = None;
+        let history = &model.history;
+
         egui.set_elapsed_time(update.since_start);
         let ctx = egui.begin_frame();
 
 
         egui::Window::new("Settings").show(&ctx, |ui| {
+            if let Some(error) = last_error {
+                ui.colored_label(egui::Color32::RED, error);
+                ui.separator();
+            }
+
             ui.label("Debug");
             ui.add(egui::Checkbox::new(&mut setttings.show_grid, "Show Grid"));
             ui.separator();
 
+            ui.label(format!("Seed: {}", setttings.seed));
+            ui.text_edit_singleline(&mut setttings.seed_text);
+
+            ui.horizontal(|ui| {
+                if ui.button("Use Seed").clicked() {
+                    setttings.seed = carbon_random::seed_from_str(&setttings.seed_text);
+                    model.is_setup = false;
+                }
+
+                if ui.button("New Seed").clicked() {
+                    setttings.seed = carbon_random::random_seed();
+                    setttings.seed_text = setttings.seed.to_string();
+                    model.is_setup = false;
+                }
+            });
+            ui.separator();
+
+            ui.horizontal(|ui| {
+                if ui.add_enabled(history.can_undo(), egui::Button::new("Undo")).clicked() {
+                    history_move = Some(HistoryMove::Undo);
+                }
+
+                if ui.add_enabled(history.can_redo(), egui::Button::new("Redo")).clicked() {
+                    history_move = Some(HistoryMove::Redo);
+                }
+            });
+
+            egui::CollapsingHeader::new("History").show(ui, |ui| {
+                egui::ScrollArea::vertical().max_height(160.0).show(ui, |ui| {
+                    for (i, snapshot) in history.entries().iter().enumerate().rev() {
+                        if ui.selectable_label(i == history.current(), &snapshot.label).clicked() {
+                            history_move = Some(HistoryMove::Jump(i));
+                        }
+                    }
+                });
+            });
+            ui.separator();
+
+            ui.horizontal(|ui| {
+                if ui.button("Regenerate").clicked() {
+                    model.is_setup = false;
+                }
+
+                if ui.button("Export SVG").clicked() {
+                    export_requested = true;
+                }
+
+                if ui.button("Save JSON").clicked() {
+                    save_json_requested = true;
+                }
+            });
+
+            #[cfg(not(target_arch = "wasm32"))]
+            ui.label("JSON file");
+            #[cfg(target_arch = "wasm32")]
+            ui.label("Paste JSON");
+            ui.horizontal(|ui| {
+                ui.text_edit_singleline(json_input);
+                if ui.button("Load JSON").clicked() {
+                    load_json_requested = true;
+                }
+            });
+            ui.separator();
+
+            ui.label("Presets");
+            egui::ComboBox::from_label("Preset")
+                .selected_text(selected_preset.as_str())
+                .show_ui(ui, |ui| {
+                    for name in preset_names.iter() {
+                        ui.selectable_value(selected_preset, name.clone(), name);
+                    }
+                });
+            ui.horizontal(|ui| {
+                ui.label("Name");
+                ui.text_edit_singleline(preset_name_input);
+            });
+            ui.horizontal(|ui| {
+                if ui.button("Load").clicked() {
+                    preset_action = Some(PresetAction::Load);
+                }
+                if ui.button("Save").clicked() {
+                    preset_action = Some(PresetAction::Save);
+                }
+                if ui.button("Rename").clicked() {
+                    preset_action = Some(PresetAction::Rename);
+                }
+                if ui.button("Delete").clicked() {
+                    preset_action = Some(PresetAction::Delete);
+                }
+            });
+            ui.separator();
+
+            ui.label("Page");
+            let page_size = setttings.page.size;
+            egui::ComboBox::from_label("Page Size")
+                .selected_text(setttings.page.size.label())
+                .show_ui(ui, |ui| {
+                    for size in PageSize::all() {
+                        ui.selectable_value(&mut setttings.page.size, size, size.label());
+                    }
+                });
+            if page_size != setttings.page.size {
+                model.is_setup = false;
+            }
+
+            let orientation = setttings.page.orientation;
+            egui::ComboBox::from_label("Orientation")
+                .selected_text(format!("{:?}", setttings.page.orientation))
+                .show_ui(ui, |ui| {
+                    ui.selectable_value(&mut setttings.page.orientation, Orientation::Portrait, "Portrait");
+                    ui.selectable_value(&mut setttings.page.orientation, Orientation::Landscape, "Landscape");
+                });
+            if orientation != setttings.page.orientation {
+                model.is_setup = false;
+            }
+
+            if setttings.page.size == PageSize::Custom {
+                if ui.add(
+                    egui::Slider::new(
+                        &mut setttings.page.custom_width, 50.0..=1000.0,
+                    ).text("Width (mm)")
+                ).changed() {
+                    model.is_setup = false;
+                }
+
+                if ui.add(
+                    egui::Slider::new(
+                        &mut setttings.page.custom_height, 50.0..=1000.0,
+                    ).text("Height (mm)")
+                ).changed() {
+                    model.is_setup = false;
+                }
+            }
+
+            if ui.add(
+                egui::Slider::new(
+                    &mut setttings.page.dpi, 72.0..=600.0,
+                ).text("DPI")
+            ).changed() {
+                model.is_setup = false;
+            }
+
+            let (page_w, page_h) = setttings.page.size_mm();
+            let (print_w, print_h) = setttings.page.print_pixels();
+            ui.label(format!(
+                "{:.0} x {:.0} mm, {} x {} px at {:.0} dpi",
+                page_w, page_h, print_w, print_h, setttings.page.dpi,
+            ));
+            ui.separator();
+
             ui.label("Layout");
-            ui.add(
+            let layout_name = setttings.layout.clone();
+            egui::ComboBox::from_label("Layout")
+                .selected_text(setttings.layout.clone())
+                .show_ui(ui, |ui| {
+                    for name in sketch_layout::strategy_names() {
+                        ui.selectable_value(&mut setttings.layout, name.to_string(), name);
+                    }
+                });
+            if layout_name != setttings.layout {
+                model.is_setup = false;
+            }
+
+            if ui.add(
                 egui::Slider::new(
-                    &mut setttings.page_padding, 0..=100,
-                ).text("Page Padding")
-            );
+                    &mut setttings.page_padding, 0.0..=50.0,
+                ).text("Page Padding (mm)")
+            ).changed() {
+                model.is_setup = false;
+            }
 
             if ui.add(
                 egui::Slider::new(
@@ -84,291 +483,353 @@ fn update(app: &App, model: &mut Model, update: Update) {
 
             if ui.add(
                 egui::Slider::new(
-                    &mut setttings.gap, 0..=30,
-                ).text("Gap")
+                    &mut setttings.gap, 0.0..=10.0,
+                ).text("Gap (mm)")
             ).changed() {
                 model.is_setup = false;
             }
-        });
-    }
-}
 
+            // parameters that only make sense for the selected strategy
+            if let Some(strategy) = sketch_layout::find_strategy(&setttings.layout) {
+                if strategy.settings_ui(ui, setttings) {
+                    model.is_setup = false;
+                }
+            }
+            ui.separator();
 
-fn generate_layout(
-    win_rect: Rect,
-    page_padding: i32,
-    rows: i32,
-    cols: i32,
-    gap: i32,
-) -> Vec<Vec<LayoutItem>> {
-    let r = Rect::from_xy_wh(
-        win_rect.xy(),
-        win_rect.wh(),
-    ).pad(page_padding.to_f32().unwrap());
-
+            ui.label("Stroke");
+            let stroke_mode = setttings.stroke_mode;
+            egui::ComboBox::from_label("Stroke Colour")
+                .selected_text(format!("{:?}", setttings.stroke_mode))
+                .show_ui(ui, |ui| {
+                    ui.selectable_value(&mut setttings.stroke_mode, StrokeMode::Palette, "Palette");
+                    ui.selectable_value(&mut setttings.stroke_mode, StrokeMode::Ink, "Ink");
+                    ui.selectable_value(&mut setttings.stroke_mode, StrokeMode::None, "None");
+                });
+            if stroke_mode != setttings.stroke_mode {
+                model.is_setup = false;
+            }
 
-    let mut layout = vec![];
+            ui.horizontal(|ui| {
+                ui.label("Ink");
+                if ui.color_edit_button_rgb(&mut setttings.stroke_ink).changed() {
+                    model.is_setup = false;
+                }
+            });
 
+            if ui.add(
+                egui::Slider::new(
+                    &mut setttings.stroke_width, 0.1..=5.0,
+                ).text("Stroke Width")
+            ).changed() {
+                model.is_setup = false;
+            }
 
-    let row_h = r.h() / rows.to_f32().unwrap();
-    let col_w = r.w() / cols.to_f32().unwrap();
+            if ui.add(
+                egui::Slider::new(
+                    &mut setttings.stroke_dash, 0.0..=20.0,
+                ).text("Dash (0 = solid)")
+            ).changed() {
+                model.is_setup = false;
+            }
 
-    let mut y = r.bottom() + row_h / 2.0;
+            if ui.add(
+                egui::Slider::new(
+                    &mut setttings.stroke_dash_gap, 0.0..=20.0,
+                ).text("Dash Gap")
+            ).changed() {
+                model.is_setup = false;
+            }
+            ui.separator();
 
-    // generate items ---------------------------------------------------
-    for _ in 0..rows {
-        let row_rect = Rect::from_xy_wh(
-            pt2(0.0, 0.0),
-            pt2(r.w(), row_h),
-        );
+            ui.collapsing("Shapes", |ui| {
+                for (text, weight) in setttings.shape_weights.entries_mut() {
+                    if ui.add(egui::Slider::new(weight, 0.0..=10.0).text(text)).changed() {
+                        model.is_setup = false;
+                    }
+                }
 
-        let mut x = row_rect.left() + col_w / 2.0;
-        let mut row_items = vec![];
-
-        // gen shapes we want
-        for _ in 0..cols {
-            row_items.push(
-                LayoutItem {
-                    shape: get_rnd_shape(),
-                    dimensions: Rect::from_xy_wh(
-                        pt2(x, y),
-                        pt2(col_w, row_h),
-                    ),
+                if ui.add(
+                    egui::Slider::new(
+                        &mut setttings.shape_weights.star_points, 3..=12,
+                    ).text("Star Points")
+                ).changed() {
+                    model.is_setup = false;
                 }
-            );
 
-            x = x + col_w;
-        }
+                if ui.add(
+                    egui::Slider::new(
+                        &mut setttings.shape_weights.ngon_sides, 3..=12,
+                    ).text("N-Gon Sides")
+                ).changed() {
+                    model.is_setup = false;
+                }
+            });
+
+            ui.collapsing("Orientation", |ui| {
+                let rotation_mode = setttings.rotation_mode;
+                egui::ComboBox::from_label("Rotation")
+                    .selected_text(format!("{:?}", setttings.rotation_mode))
+                    .show_ui(ui, |ui| {
+                        ui.selectable_value(&mut setttings.rotation_mode, RotationMode::None, "None");
+                        ui.selectable_value(&mut setttings.rotation_mode, RotationMode::Random, "Random");
+                        ui.selectable_value(&mut setttings.rotation_mode, RotationMode::Fixed, "Fixed");
+                        ui.selectable_value(&mut setttings.rotation_mode, RotationMode::TowardCentre, "Toward Centre");
+                        ui.selectable_value(&mut setttings.rotation_mode, RotationMode::AwayFromCentre, "Away From Centre");
+                    });
+                if rotation_mode != setttings.rotation_mode {
+                    model.is_setup = false;
+                }
 
-        let mut new_row_items = vec![];
-        let mut new_layout_item: Option<LayoutItem> = None;
-        //.pad(gap.to_f32().unwrap())
-
-        // join the squares together ------------------------------------------------
-        for x_item in row_items {
-            if x_item.shape == Shapes::Square {
-                if new_layout_item.is_none() {
-                    new_layout_item = Some(
-                        LayoutItem {
-                            shape: x_item.shape,
-                            dimensions: x_item.dimensions,
-                        }
-                    );
-                } else {
-                    // we have one already streatch out
-                    let t_item = new_layout_item.unwrap();
-                    let w1 = t_item.dimensions.w();
-                    let r = t_item.dimensions.right();
-                    let new_r = t_item.dimensions
-                        .stretch_to_point(
-                            [
-                                x_item.dimensions.right(),
-                                x_item.dimensions.top(),
-                            ],
-                        );
-
-                    let df = LayoutItem {
-                        shape: x_item.shape,
-                        dimensions: Rect::from_xy_wh(new_r.xy(), new_r.wh()),
-                    };
-
-                    new_layout_item = Some(df);
+                if ui.add(egui::Checkbox::new(&mut setttings.rotation_quarter_turns, "Random in 90° Steps")).changed() {
+                    model.is_setup = false;
                 }
-            } else {
-                // where we just working with a square
 
-                if new_layout_item.is_some() {
-                    new_row_items.push(
-                        new_layout_item.unwrap().clone()
-                    );
+                if ui.add(
+                    egui::Slider::new(
+                        &mut setttings.fixed_rotation, 0.0..=360.0,
+                    ).text("Fixed Rotation")
+                ).changed() {
+                    model.is_setup = false;
                 }
-                new_layout_item = None;
 
-                new_layout_item = Some(
-                    LayoutItem {
-                        shape: x_item.shape,
-                        dimensions: x_item.dimensions,
+                ui.horizontal(|ui| {
+                    if ui.add(egui::Checkbox::new(&mut setttings.flip_x, "Flip X")).changed() {
+                        model.is_setup = false;
+                    }
+                    if ui.add(egui::Checkbox::new(&mut setttings.flip_y, "Flip Y")).changed() {
+                        model.is_setup = false;
                     }
-                );
+                });
+            });
 
-                new_row_items.push(
-                    new_layout_item.unwrap().clone()
-                );
-                new_layout_item = None;
-            }
-        }
-        if (new_layout_item.is_some()) {
-            new_row_items.push(
-                new_layout_item.unwrap().clone()
-            );
-        }
+            ui.collapsing("Fill Styles", |ui| {
+                for (text, weight) in setttings.fill_style_weights.entries_mut() {
+                    if ui.add(egui::Slider::new(weight, 0.0..=10.0).text(text)).changed() {
+                        model.is_setup = false;
+                    }
+                }
+            });
+
+            ui.collapsing("Roughness", |ui| {
+                let rough_sliders = [
+                    (&mut setttings.roughness, 0.0..=10.0, "Roughness"),
+                    (&mut setttings.bowing, 0.0..=20.0, "Bowing"),
+                    (&mut setttings.max_randomness_offset, 0.0..=20.0, "Max Randomness Offset"),
+                    (&mut setttings.curve_tightness, -1.0..=1.0, "Curve Tightness"),
+                    (&mut setttings.curve_fitting, 0.0..=1.0, "Curve Fitting"),
+                    (&mut setttings.hachure_angle, -90.0..=90.0, "Hachure Angle"),
+                    (&mut setttings.hachure_gap, -1.0..=30.0, "Hachure Gap (-1 = auto)"),
+                    (&mut setttings.fill_weight, -1.0..=10.0, "Fill Weight (-1 = auto)"),
+                    (&mut setttings.dash_offset, -1.0..=30.0, "Dash Offset (-1 = auto)"),
+                    (&mut setttings.dash_gap, -1.0..=30.0, "Dash Gap (-1 = auto)"),
+                    (&mut setttings.zigzag_offset, -1.0..=30.0, "Zigzag Offset (-1 = auto)"),
+                ];
+
+                for (value, range, text) in rough_sliders {
+                    if ui.add(egui::Slider::new(value, range).text(text)).changed() {
+                        model.is_setup = false;
+                    }
+                }
+            });
+        });
 
+        // the frame holds on to model.e_gui until it ends, end it before
+        // the requests below touch the model
+        drop(ctx);
 
-        // add padding
-        let mut padded_row = vec![];
-        for non_padded in new_row_items {
-            let d = non_padded.dimensions.pad(gap.to_f32().unwrap());
-            padded_row.push(
-                LayoutItem {
-                    shape: non_padded.shape,
-                    dimensions: d,
-                }
-            )
+        if export_requested && model.scene.is_some() {
+            sketch_svg::export_svg(model.scene.as_ref().unwrap(), &svg_file_name(model));
         }
 
-        layout.push(padded_row);
-        y = y + row_h;
-    }
+        if save_json_requested {
+            export_composition(model);
+        }
 
-    layout
-}
+        if load_json_requested {
+            load_composition(model);
+        }
 
+        if let Some(history_move) = history_move {
+            move_in_history(model, history_move);
+        }
 
-fn get_rnd_shape() -> Shapes {
-    let s: Shapes;
-    match random_range(0, 10) {
-        0 => s = Shapes::Circle,
-        1 => s = Shapes::Triangle,
-        _ => s = Shapes::Square,
+        if let Some(action) = preset_action {
+            run_preset_action(model, action);
+        }
     }
-    s
 }
 
-fn view(app: &App, model: &Model, frame: nannou::Frame) {
-    if !model.is_setup {
-        return; // not ready exit
-    }
 
-    if model.render_complete {
+fn key_pressed(_app: &App, model: &mut Model, key: Key) {
+    // don't steal keys while typing in the egui panel
+    if model.e_gui.is_some() && model.e_gui.as_ref().unwrap().ctx().wants_keyboard_input() {
         return;
     }
 
-    // get the working drawing object
-    // let worker = model.high_res_worker.as_ref();
-    // let high: &HigResWorker = worker.unwrap();
-    // let worker_draw = &high.draw;
+    match key {
+        Key::S => {
+            if model.scene.is_some() {
+                sketch_svg::export_svg(model.scene.as_ref().unwrap(), &svg_file_name(model));
+            }
+        }
+        Key::J => {
+            export_composition(model);
+        }
+        Key::Z => {
+            move_in_history(model, HistoryMove::Undo);
+        }
+        Key::Y => {
+            move_in_history(model, HistoryMove::Redo);
+        }
+        _ => {}
+    }
+}
 
 
-    let win_rect = app.window_rect();
+// millimetres to pixels, around the page centre --------------------------
+fn scale_layout(layout: &mut [Vec<LayoutItem>], scale: f32) {
+    for item in layout.iter_mut().flatten() {
+        item.dimensions = Rect::from_xy_wh(
+            item.dimensions.xy() * scale,
+            item.dimensions.wh() * scale,
+        );
 
-    let draw = app.draw();
-    draw.background().color(WHITE);
-
-    let mut c = 0;
-    let layouts = model.layout.as_ref().unwrap();
-    for row in layouts {
-        for item in row {
-            // draw.rect()
-            //     .color(gray(0.8))
-            //     .xy(item.dimensions.xy())
-            //     .wh(item.dimensions.wh());
-
-            let p = model.raw_palette.as_ref().unwrap();
-            let r = random_range(0, 5);
-            let fill_color = p[r].clone();
-
-            let parse_color = fill_color.parse::<csscolorparser::Color>().unwrap();
-
-            let sc = Srgba::from_components((
-                parse_color.r,
-                parse_color.g,
-                parse_color.b,
-                parse_color.a)
-            );
+        if let Some(cell) = item.cell.as_mut() {
+            for p in cell.iter_mut() {
+                *p = *p * scale;
+            }
+        }
+    }
+}
 
-            let mut fill_style = FillStyle::ZigZag;
+// rotate / flip the items -------------------------------------------------
+fn orient_layout(layout: &mut [Vec<LayoutItem>], page: Rect, settings: &Settings) {
+    let mut transform_rng = carbon_random::stream_rng(settings.seed, RandomStream::Transform);
 
+    for item in layout.iter_mut().flatten() {
+        // direction from the item to the middle of the page, 0 = pointing right
+        let to_centre = (page.y() - item.dimensions.y())
+            .atan2(page.x() - item.dimensions.x())
+            .to_degrees();
 
-            match random_range(0,5) {
+        item.transform = match settings.rotation_mode {
+            RotationMode::None => ItemTransform::default(),
 
-                0 => fill_style = FillStyle::Dashed,
-                1 => fill_style = FillStyle::Dots,
-                2 => fill_style = FillStyle::Hachure,
-                3 => fill_style = FillStyle::CrossHatch,
-                4 => fill_style = FillStyle::ZigZagLine,
-                _ => fill_style = FillStyle::ZigZag,
-            }
+            RotationMode::Random => {
+                let rotation = if settings.rotation_quarter_turns {
+                    transform_rng.gen_range(0..4) as f32 * 90.0
+                } else {
+                    transform_rng.gen_range(0.0..360.0)
+                };
 
-            let options = OptionsBuilder::default()
-                .seed(c * 1000)
-                .fill(sc.into_format())
-                .fill_style(fill_style.clone())
-
-                // .stroke()
-                // .curve_tightness(settings.curve_tightness)
-                // .curve_fitting(settings.curve_fitting)
-                // .bowing(settings.bowing)
-                // .max_randomness_offset(settings.max_ran)
-                // .roughness(settings.roughness)
-
-                .build()
-                .unwrap();
-
-            let g = Generator::default();
-
-            let draw_item: Drawable<f32>;
-
-            match item.shape {
-                Shapes::Square => {
-                    draw_item = g.rectangle::<f32>(
-                        item.dimensions.x() - (item.dimensions.w() / 2.0),
-                        item.dimensions.y() - (item.dimensions.h() / 2.0),
-                        item.dimensions.w(),
-                        item.dimensions.h(),
-                        &Some(options.clone()),
-                    );
+                ItemTransform {
+                    rotation,
+                    flip_x: transform_rng.gen_bool(0.5),
+                    flip_y: transform_rng.gen_bool(0.5),
                 }
+            }
 
-                Shapes::Circle => {
-                    draw_item = g.ellipse::<f32>(
-                        item.dimensions.x(),
-                        item.dimensions.y(),
-                        item.dimensions.w(),
-                        item.dimensions.h(),
-                        &Some(options.clone()),
-                    );
+            RotationMode::Fixed => ItemTransform {
+                rotation: settings.fixed_rotation,
+                flip_x: settings.flip_x,
+                flip_y: settings.flip_y,
+            },
+
+            // triangles point up by default, so take 90 off the direction
+            RotationMode::TowardCentre | RotationMode::AwayFromCentre => {
+                if item.shape == Shapes::Triangle {
+                    let away = if settings.rotation_mode == RotationMode::AwayFromCentre { 180.0 } else { 0.0 };
+                    ItemTransform {
+                        rotation: to_centre - 90.0 + away,
+                        flip_x: false,
+                        flip_y: false,
+                    }
+                } else {
+                    ItemTransform::default()
                 }
+            }
+        };
+    }
+}
 
-                Shapes::Triangle => {
-                    let top = Point2D::new(
-                        item.dimensions.x(),
-                        item.dimensions.y() + (item.dimensions.h() / 2.0),
-                    );
 
-                    let left = Point2D::new(
-                        item.dimensions.x() - (item.dimensions.w() / 2.0),
-                        item.dimensions.y() - (item.dimensions.h() / 2.0),
-                    );
+// give every item its own colour, fill style and roughr seed ----------------
+fn style_layout(layout: &mut [Vec<LayoutItem>], palette: &[String; 5], settings: &Settings) {
+    let seed = settings.seed;
+    let mut colour_rng = carbon_random::stream_rng(seed, RandomStream::Colour);
+    let mut stroke_rng = carbon_random::stream_rng(seed, RandomStream::Stroke);
+    let mut fill_style_rng = carbon_random::stream_rng(seed, RandomStream::FillStyle);
+    let mut roughr_rng = carbon_random::stream_rng(seed, RandomStream::Roughr);
 
+    let [ink_r, ink_g, ink_b] = settings.stroke_ink;
 
-                    let right = Point2D::new(
-                        item.dimensions.x() + (item.dimensions.w() / 2.0),
-                        item.dimensions.y() - (item.dimensions.h() / 2.0),
-                    );
+    for row in layout.iter_mut() {
+        for item in row.iter_mut() {
+            // styles loaded from a file are kept, only their roughr options
+            // are rebuilt
+            if let Some(style) = item.style.as_mut() {
+                style.options = style.roughr_options(settings);
+                continue;
+            }
 
-                    draw_item = g.polygon::<f32>(
-                        &[top, left, right],
-                        &Some(options.clone()),
-                    );
-                }
+            let r = carbon_random::pick_index(&mut colour_rng, palette.len());
+            let fill_colour = carbon::carbon_colors::parse_colour(&palette[r]);
 
-                _ => {
-                    draw_item = g.rectangle::<f32>(
-                        item.dimensions.x() - (item.dimensions.w() / 2.0),
-                        item.dimensions.y() - (item.dimensions.h() / 2.0),
-                        item.dimensions.w(),
-                        item.dimensions.h(),
-                        &Some(options.clone()),
-                    );
+            let stroke_colour = match settings.stroke_mode {
+                StrokeMode::Palette => {
+                    let s = carbon_random::pick_index(&mut stroke_rng, palette.len());
+                    Some(carbon::carbon_colors::parse_colour(&palette[s]))
                 }
-            }
+                StrokeMode::Ink => Some(Srgba::new(ink_r, ink_g, ink_b, 1.0)),
+                StrokeMode::None => None,
+            };
+
+            let fill_style = settings.fill_style_weights.pick(&mut fill_style_rng);
 
-            sketch_lines(&draw, &draw_item);
+            let item_seed = roughr_rng.gen::<u64>();
 
+            let mut style = ItemStyle {
+                fill_colour,
+                stroke_colour,
+                fill_style,
+                seed: item_seed,
+                options: Options::default(),
+            };
+            style.options = style.roughr_options(settings);
 
-            c = c + 1;
+            item.style = Some(style);
         }
     }
+}
+
+
+fn view(app: &App, model: &Model, frame: nannou::Frame) {
+    if !model.is_setup {
+        return; // not ready exit
+    }
+
+    // get the working drawing object
+    // let worker = model.high_res_worker.as_ref();
+    // let high: &HigResWorker = worker.unwrap();
+    // let worker_draw = &high.draw;
+
+
+    let win_rect = app.window_rect();
+
+    let draw = app.draw();
+
+    // everything was generated up front, we only draw the cached state here.
+    // The page is shrunk (or grown) to fit the window, grey shows around it.
+    let scene = model.scene.as_ref().unwrap();
+    let preview_scale = (win_rect.w() / scene.width).min(win_rect.h() / scene.height);
+
+    draw.background().color(gray(0.85));
+    draw.rect()
+        .w_h(scene.width * preview_scale, scene.height * preview_scale)
+        .color(WHITE);
+
+    draw_scene(&draw.scale(preview_scale), scene);
 
     if model.settings.show_grid {
         carbon_sketch_helpers::draw_grid(&draw, &win_rect, 20.0, 1.0);
@@ -405,7 +866,7 @@ async fn create_window(app: &App) {
         .device_descriptor(device_desc)
         .title("nn_001")
         .raw_event(raw_window_event)
-        // .key_pressed(key_pressed)
+        .key_pressed(key_pressed)
         // .key_released(key_released)
         // .mouse_pressed(mouse_pressed)
         // .mouse_moved(mouse_moved)
@@ -419,161 +880,25 @@ async fn create_window(app: &App) {
 }
 
 
-fn sketch_lines(draw: &Draw, lp: &Drawable<f32>) {
-    for set in lp.sets.iter() {
-        if set.op_set_type == OpSetType::Path {
-            let working_set = set.clone();
-            // println!("{:?}", working_set);
-
-            let mut points = Vec::new();
-
-            // let mut start_pont = pt2(0.0, 0.0);
-
-            for item in working_set.ops {
-                match item.op {
-                    OpType::Move => {
+fn draw_scene(draw: &Draw, scene: &Scene) {
+    for path in scene.paths.iter() {
+        for segment in path.segments.iter() {
+            let points: Vec<Point2> = segment.iter()
+                .map(|p| pt2(p.x, p.y))
+                .collect();
 
-                        // if we are about to move - draw the points
-                        if !points.is_empty() {
-                            draw.polyline()
-                                .color(gray(0.7))
-                                .points(points.clone());
-                            points.clear();
-                        }
-
-                        points.push(pt2(
-                            item.data[0].to_f32().unwrap(),
-                            item.data[1].to_f32().unwrap(),
-                        ));
-                    }
-                    OpType::LineTo => {
-                        points.push(pt2(
-                            item.data[0].to_f32().unwrap(),
-                            item.data[1].to_f32().unwrap(),
-                        ));
-                    }
-                    OpType::BCurveTo => {
-                        let mut curve_points = Vec::new();
-                        let last_point = points.last().clone().unwrap();
-                        curve_points.push(Point2D::new(
-                            last_point.x,
-                            last_point.y,
-                        ));
-
-                        curve_points.push(Point2D::new(
-                            item.data[0].to_f32().unwrap(),
-                            item.data[1].to_f32().unwrap(),
-                        ));
-
-                        curve_points.push(Point2D::new(
-                            item.data[2].to_f32().unwrap(),
-                            item.data[3].to_f32().unwrap(),
-                        ));
-
-                        curve_points.push(Point2D::new(
-                            item.data[4].to_f32().unwrap(),
-                            item.data[5].to_f32().unwrap(),
-                        ));
-
-                        let result_015 = points_on_bezier_curves(&curve_points, 0.2, Some(0.01));
-
-                        for p in result_015 {
-                            points.push(pt2(p.x, p.y))
-                        }
-                    }
-                }
+            if let Some(fill) = path.fill {
+                draw.polygon()
+                    .color(srgba(fill.red, fill.green, fill.blue, fill.alpha))
+                    .points(points.clone());
             }
 
-            draw.polyline()
-                .color(gray(0.7))
-                .points(points.clone());
-            points.clear();
-        }
-
-        if set.op_set_type == OpSetType::FillSketch {
-            let working_set = set.clone();
-            // println!("{:?}", working_set);
-
-            let sb_fill = lp.options.fill.unwrap();
-
-
-            let mut points = Vec::new();
-
-            // let mut start_pont = pt2(0.0, 0.0);
-
-            for item in working_set.ops {
-                match item.op {
-                    OpType::Move => {
-
-                        // if we are about to move - draw the points
-                        if !points.is_empty() {
-                            draw.polyline()
-                                .weight(2.0)
-                                .color(
-                                    srgba(sb_fill.red, sb_fill.green, sb_fill.blue, sb_fill.alpha)
-                                )
-                                .points(points.clone());
-                            points.clear();
-                        }
-
-                        points.push(pt2(
-                            item.data[0].to_f32().unwrap(),
-                            item.data[1].to_f32().unwrap(),
-                        ));
-                    }
-                    OpType::LineTo => {
-                        points.push(pt2(
-                            item.data[0].to_f32().unwrap(),
-                            item.data[1].to_f32().unwrap(),
-                        ));
-                    }
-                    OpType::BCurveTo => {
-                        let mut curve_points = Vec::new();
-                        let last_point = points.last().clone().unwrap();
-                        curve_points.push(Point2D::new(
-                            last_point.x,
-                            last_point.y,
-                        ));
-
-                        curve_points.push(Point2D::new(
-                            item.data[0].to_f32().unwrap(),
-                            item.data[1].to_f32().unwrap(),
-                        ));
-
-                        curve_points.push(Point2D::new(
-                            item.data[2].to_f32().unwrap(),
-                            item.data[3].to_f32().unwrap(),
-                        ));
-
-                        curve_points.push(Point2D::new(
-                            item.data[4].to_f32().unwrap(),
-                            item.data[5].to_f32().unwrap(),
-                        ));
-
-                        let result_015 = points_on_bezier_curves(&curve_points, 0.2, Some(0.01));
-
-                        for p in result_015 {
-                            points.push(pt2(p.x, p.y))
-                        }
-                    }
-                }
+            if let Some(stroke) = path.stroke {
+                draw.polyline()
+                    .weight(path.stroke_weight)
+                    .color(srgba(stroke.red, stroke.green, stroke.blue, stroke.alpha))
+                    .points(points);
             }
-
-
-            draw.polyline()
-                .weight(2.0)
-                .color(
-                    srgba(sb_fill.red, sb_fill.green, sb_fill.blue, sb_fill.alpha)
-                )
-                .points(points.clone());
-            points.clear();
-        }
-
-        if set.op_set_type == OpSetType::FillPath {
-            let working_set = set.clone();
-            println!("{:?}", working_set);
-
-            // let mut points = Vec::new();
         }
     }
 }
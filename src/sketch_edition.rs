@@ -0,0 +1,220 @@
+use serde::Serialize;
+
+use crate::carbon::carbon_random;
+use crate::sketch;
+use crate::sketch_model::{Model, Settings};
+use crate::sketch_png;
+use crate::sketch_svg;
+use crate::sketch_traits::{self, Traits};
+
+// Headless batch runs ("editions"): one piece per seed from the same settings,
+// each saved as svg and / or png with a json sidecar that is enough to make
+// it again, or just a trait report over the seeds. Native only, nothing here
+// needs a window.
+
+#[derive(PartialEq, Clone, Copy, Debug)]
+pub enum EditionFormat {
+    Svg,
+    Png,
+    Both,
+}
+
+impl EditionFormat {
+    pub fn from_name(name: &str) -> Option<EditionFormat> {
+        match name {
+            "svg" => Some(EditionFormat::Svg),
+            "png" => Some(EditionFormat::Png),
+            "both" => Some(EditionFormat::Both),
+            _ => None,
+        }
+    }
+}
+
+#[derive(PartialEq, Clone, Copy, Debug)]
+pub enum ReportFormat {
+    Csv,
+    Markdown,
+    Both,
+}
+
+impl ReportFormat {
+    pub fn from_name(name: &str) -> Option<ReportFormat> {
+        match name {
+            "csv" => Some(ReportFormat::Csv),
+            "md" => Some(ReportFormat::Markdown),
+            "both" => Some(ReportFormat::Both),
+            _ => None,
+        }
+    }
+}
+
+// written next to every output
+#[derive(Serialize)]
+pub struct Sidecar<'a> {
+    pub edition: usize,
+    pub edition_size: usize,
+    pub seed: u64,
+    pub seed_text: &'a str,
+    pub palette: &'a [String; 5],
+    pub settings: &'a Settings,
+}
+
+// `1..100` (end excluded), `1..=100`, or a comma list of numbers / words
+// like `7, 42, sunrise`
+pub fn parse_seeds(text: &str) -> Result<Vec<String>, String> {
+    let text = text.trim();
+
+    if let Some((start, end)) = text.split_once("..") {
+        let (end, inclusive) = match end.strip_prefix('=') {
+            Some(end) => (end, true),
+            None => (end, false),
+        };
+        let start: u64 = start.trim().parse()
+            .map_err(|_| format!("bad seed range start {:?}", start))?;
+        let end: u64 = end.trim().parse()
+            .map_err(|_| format!("bad seed range end {:?}", end))?;
+
+        let seeds: Vec<String> = if inclusive {
+            (start..=end).map(|s| s.to_string()).collect()
+        } else {
+            (start..end).map(|s| s.to_string()).collect()
+        };
+        if seeds.is_empty() {
+            return Err(format!("seed range {} is empty", text));
+        }
+        return Ok(seeds);
+    }
+
+    let seeds: Vec<String> = text.split(',')
+        .map(|s| s.trim().to_string())
+        .filter(|s| !s.is_empty())
+        .collect();
+    if seeds.is_empty() {
+        return Err("no seeds given".to_string());
+    }
+    Ok(seeds)
+}
+
+// file name for one piece without the extension, numbered so the files sort
+// in edition order
+pub fn output_stem(prefix: &str, edition: usize, edition_size: usize, seed_text: &str) -> String {
+    let digits = edition_size.to_string().len().max(3);
+    let seed: String = seed_text.chars()
+        .map(|c| if c.is_alphanumeric() || c == '-' || c == '_' { c } else { '_' })
+        .collect();
+    format!("{}_{:0width$}_{}", prefix, edition, seed, width = digits)
+}
+
+fn generate_seed(settings: &Settings, seed_text: &str) -> Model {
+    let mut model = Model::new();
+    model.settings = settings.clone();
+    model.settings.seed = carbon_random::seed_from_str(seed_text);
+    model.settings.seed_text = seed_text.to_string();
+
+    sketch::generate(&mut model);
+    model
+}
+
+// makes every piece, returns the files written
+pub fn run_edition(
+    settings: &Settings,
+    seeds: &[String],
+    format: EditionFormat,
+    out_dir: &str,
+    prefix: &str,
+) -> Result<Vec<String>, String> {
+    std::fs::create_dir_all(out_dir)
+        .map_err(|e| format!("could not make {}: {}", out_dir, e))?;
+
+    let mut written = vec![];
+
+    for (i, seed_text) in seeds.iter().enumerate() {
+        let edition = i + 1;
+
+        let model = generate_seed(settings, seed_text);
+        let scene = model.scene.as_ref().unwrap();
+
+        let stem = std::path::Path::new(out_dir)
+            .join(output_stem(prefix, edition, seeds.len(), seed_text))
+            .to_string_lossy()
+            .to_string();
+
+        if format != EditionFormat::Png {
+            let file_path = format!("{}.svg", stem);
+            sketch_svg::save_svg(scene, &file_path)
+                .map_err(|e| format!("could not save {}: {}", file_path, e))?;
+            written.push(file_path);
+        }
+
+        if format != EditionFormat::Svg {
+            let file_path = format!("{}.png", stem);
+            let (w, h) = model.settings.page.print_pixels();
+            sketch_png::save_png(scene, w, h, &file_path)?;
+            written.push(file_path);
+        }
+
+        let sidecar = Sidecar {
+            edition,
+            edition_size: seeds.len(),
+            seed: model.settings.seed,
+            seed_text,
+            palette: model.raw_palette.as_ref().unwrap(),
+            settings: &model.settings,
+        };
+        let file_path = format!("{}.json", stem);
+        std::fs::write(&file_path, serde_json::to_string_pretty(&sidecar).unwrap())
+            .map_err(|e| format!("could not save {}: {}", file_path, e))?;
+        written.push(file_path);
+    }
+
+    Ok(written)
+}
+
+// Generates every seed without saving the pieces, writes the traits of each
+// one as json and the histograms over all of them. Returns the traits.
+pub fn run_report(
+    settings: &Settings,
+    seeds: &[String],
+    format: ReportFormat,
+    out_dir: &str,
+    prefix: &str,
+) -> Result<Vec<Traits>, String> {
+    std::fs::create_dir_all(out_dir)
+        .map_err(|e| format!("could not make {}: {}", out_dir, e))?;
+
+    let mut all_traits = vec![];
+
+    for (i, seed_text) in seeds.iter().enumerate() {
+        let model = generate_seed(settings, seed_text);
+        let traits = sketch_traits::collect_traits(
+            &model.settings,
+            model.raw_palette.as_ref().unwrap(),
+            model.layout.as_ref().unwrap(),
+        );
+
+        let file_path = std::path::Path::new(out_dir)
+            .join(format!("{}.traits.json", output_stem(prefix, i + 1, seeds.len(), seed_text)));
+        std::fs::write(&file_path, serde_json::to_string_pretty(&traits).unwrap())
+            .map_err(|e| format!("could not save {}: {}", file_path.display(), e))?;
+
+        all_traits.push(traits);
+    }
+
+    let histograms = sketch_traits::histograms(&all_traits);
+    let mut reports = vec![];
+    if format != ReportFormat::Markdown {
+        reports.push(("csv", sketch_traits::histograms_to_csv(&histograms, seeds.len())));
+    }
+    if format != ReportFormat::Csv {
+        reports.push(("md", sketch_traits::histograms_to_markdown(&histograms, seeds.len())));
+    }
+
+    for (extension, text) in reports {
+        let file_path = std::path::Path::new(out_dir)
+            .join(format!("{}_traits.{}", prefix, extension));
+        std::fs::write(&file_path, text)
+            .map_err(|e| format!("could not save {}: {}", file_path.display(), e))?;
+    }
+
+    Ok(all_traits)
+}
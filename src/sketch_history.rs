@@ -0,0 +1,115 @@
+use crate::sketch_model::{LayoutItem, Settings};
+
+// Every generation of the session (and every loaded piece) is kept so undo /
+// redo and the history list can bring it back exactly, without generating
+// again. The settings hold the seed, the layout holds every item as drawn.
+//
+// There is no way to lock single items, so a snapshot has no locked items of
+// its own: the whole drawn layout is kept instead. Should locking be added,
+// the flag belongs on LayoutItem and comes along with the layout.
+
+pub const HISTORY_LIMIT: usize = 50;
+
+#[derive(Clone)]
+pub struct Snapshot {
+    pub label: String,
+    pub settings: Settings,
+    pub palette: [String; 5],
+    pub layout: Vec<Vec<LayoutItem>>,
+}
+
+pub struct History {
+    entries: Vec<Snapshot>,
+    current: usize,
+    limit: usize,
+    // snapshots pushed this session, numbers the list even after trimming
+    pushed: usize,
+}
+
+impl History {
+    pub fn new(limit: usize) -> History {
+        History {
+            entries: vec![],
+            current: 0,
+            limit: limit.max(1),
+            pushed: 0,
+        }
+    }
+
+    // a new generation drops everything that was undone, the oldest entries
+    // go once the limit is reached
+    pub fn push(&mut self, snapshot: Snapshot) {
+        if !self.entries.is_empty() {
+            self.entries.truncate(self.current + 1);
+        }
+        self.entries.push(snapshot);
+
+        if self.entries.len() > self.limit {
+            let extra = self.entries.len() - self.limit;
+            self.entries.drain(0..extra);
+        }
+        self.current = self.entries.len() - 1;
+        self.pushed += 1;
+    }
+
+    // a slider drag regenerates every frame, it only needs one entry. Only
+    // the newest entry is replaced, after an undo the change is a new branch.
+    pub fn replace_current(&mut self, snapshot: Snapshot) {
+        if self.can_redo() {
+            self.push(snapshot);
+            return;
+        }
+
+        match self.entries.get_mut(self.current) {
+            Some(current) => {
+                let label = current.label.clone();
+                *current = Snapshot { label, ..snapshot };
+            }
+            None => self.push(snapshot),
+        }
+    }
+
+    pub fn undo(&mut self) -> Option<&Snapshot> {
+        if !self.can_undo() {
+            return None;
+        }
+        self.current -= 1;
+        self.entries.get(self.current)
+    }
+
+    pub fn redo(&mut self) -> Option<&Snapshot> {
+        if !self.can_redo() {
+            return None;
+        }
+        self.current += 1;
+        self.entries.get(self.current)
+    }
+
+    pub fn jump(&mut self, index: usize) -> Option<&Snapshot> {
+        if index >= self.entries.len() {
+            return None;
+        }
+        self.current = index;
+        self.entries.get(self.current)
+    }
+
+    pub fn can_undo(&self) -> bool {
+        self.current > 0
+    }
+
+    pub fn can_redo(&self) -> bool {
+        self.current + 1 < self.entries.len()
+    }
+
+    pub fn entries(&self) -> &[Snapshot] {
+        &self.entries
+    }
+
+    pub fn current(&self) -> usize {
+        self.current
+    }
+
+    pub fn pushed(&self) -> usize {
+        self.pushed
+    }
+}
@@ -0,0 +1,125 @@
+use serde::{Deserialize, Serialize};
+
+use crate::sketch_model::{LayoutItem, Model, Settings};
+
+// A whole piece as json: the settings, the palette and every item with its
+// resolved style. Loading it draws the same artwork without generating.
+//
+// Items are in css pixels around the page centre (y up), like the scene.
+// Only `layout` is required, so hand written files can leave out the
+// settings / palette (the current ones are kept) or the item styles (they are
+// picked from the palette and seed like a generated piece).
+#[derive(Clone, Serialize, Deserialize)]
+pub struct Composition {
+    #[serde(default = "format_version")]
+    pub version: u32,
+    #[serde(default)]
+    pub settings: Option<Settings>,
+    #[serde(default)]
+    pub palette: Option<[String; 5]>,
+    pub layout: Vec<Vec<LayoutItem>>,
+}
+
+pub const FORMAT_VERSION: u32 = 1;
+
+fn format_version() -> u32 {
+    FORMAT_VERSION
+}
+
+pub fn composition_to_json(
+    settings: &Settings,
+    palette: &[String; 5],
+    layout: &[Vec<LayoutItem>],
+) -> String {
+    let composition = Composition {
+        version: FORMAT_VERSION,
+        settings: Some(settings.clone()),
+        palette: Some(palette.clone()),
+        layout: layout.to_vec(),
+    };
+
+    serde_json::to_string_pretty(&composition).unwrap()
+}
+
+pub fn composition_from_json(text: &str) -> Result<Composition, String> {
+    let mut value: serde_json::Value = serde_json::from_str(text)
+        .map_err(|e| format!("not a composition: {}", e))?;
+
+    // the settings go over the defaults, so pieces saved before a setting
+    // was added still load
+    let settings = match value.as_object_mut().and_then(|o| o.remove("settings")) {
+        None | Some(serde_json::Value::Null) => None,
+        Some(settings) => Some(
+            settings_from_value(settings)
+                .map_err(|e| format!("bad settings: {}", e))?
+        ),
+    };
+
+    let mut composition: Composition = serde_json::from_value(value)
+        .map_err(|e| format!("not a composition: {}", e))?;
+    composition.settings = settings;
+
+    if composition.version > FORMAT_VERSION {
+        return Err(format!(
+            "made by a newer version (format {}, this reads {})",
+            composition.version, FORMAT_VERSION,
+        ));
+    }
+
+    Ok(composition)
+}
+
+// settings laid over the defaults, anything missing keeps its default value
+pub fn settings_from_value(over: serde_json::Value) -> Result<Settings, String> {
+    let mut settings = serde_json::to_value(Model::new().settings).unwrap();
+
+    merge_json(&mut settings, over);
+
+    serde_json::from_value(settings).map_err(|e| e.to_string())
+}
+
+fn merge_json(base: &mut serde_json::Value, over: serde_json::Value) {
+    match (base, over) {
+        (serde_json::Value::Object(base), serde_json::Value::Object(over)) => {
+            for (key, value) in over {
+                match base.get_mut(&key) {
+                    Some(existing) => merge_json(existing, value),
+                    None => {
+                        base.insert(key, value);
+                    }
+                }
+            }
+        }
+        (base, over) => *base = over,
+    }
+}
+
+
+pub fn save_json(text: &str, file_path: &str) -> std::io::Result<()> {
+    std::fs::write(file_path, text)
+}
+
+pub fn load_json(file_path: &str) -> Result<Composition, String> {
+    let text = std::fs::read_to_string(file_path)
+        .map_err(|e| format!("could not read {}: {}", file_path, e))?;
+    composition_from_json(&text)
+}
+
+
+// same as the svg export, a file next to the binary or a browser download
+#[cfg(not(target_arch = "wasm32"))]
+pub fn export_json(text: &str, file_name: &str) {
+    match save_json(text, file_name) {
+        Ok(_) => println!("saved {}", file_name),
+        Err(e) => println!("could not save {}: {}", file_name, e),
+    }
+}
+
+#[cfg(target_arch = "wasm32")]
+pub fn export_json(text: &str, file_name: &str) {
+    let data_url = format!(
+        "data:application/json;charset=utf-8,{}",
+        crate::sketch_svg::percent_encode(text),
+    );
+    crate::sketch_svg::download(&data_url, file_name);
+}
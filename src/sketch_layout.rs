@@ -0,0 +1,1357 @@
+use nannou::prelude::*;
+use nannou_egui::egui;
+use rand_chacha::ChaCha12Rng;
+use rand::Rng;
+
+use crate::sketch_model::{BlockMerge, BspSettings, LayoutItem, PackingSettings, PointSeeding, PoissonSettings, Settings, ShapeWeights, Shapes, TessellationSettings, TrackMode, TrackSizes};
+use crate::carbon::carbon_polygon;
+use crate::carbon::carbon_random;
+use crate::carbon::carbon_tessellation;
+
+// One way of cutting the page up into layout items. The shape / style / scene
+// passes only look at the items, so any strategy draws the same way.
+pub trait LayoutStrategy {
+    // key used by the egui combo, `--layout` and saved settings
+    fn name(&self) -> &'static str;
+
+    fn generate(
+        &self,
+        page: Rect,
+        settings: &Settings,
+        shape_rng: &mut ChaCha12Rng,
+        layout_rng: &mut ChaCha12Rng,
+    ) -> Vec<Vec<LayoutItem>>;
+
+    // controls for the strategy's own parameters, true when one changed
+    fn settings_ui(&self, ui: &mut egui::Ui, settings: &mut Settings) -> bool;
+}
+
+
+// every strategy we know about, the first one is the default
+pub fn strategies() -> Vec<Box<dyn LayoutStrategy>> {
+    vec![
+        Box::new(GridLayout),
+        Box::new(BspLayout),
+        Box::new(HexLayout),
+        Box::new(TriangleLayout),
+        Box::new(CirclePackingLayout),
+        Box::new(PoissonLayout),
+        Box::new(VoronoiLayout),
+        Box::new(DelaunayLayout),
+    ]
+}
+
+pub fn strategy_names() -> Vec<&'static str> {
+    strategies().iter().map(|s| s.name()).collect()
+}
+
+pub fn find_strategy(name: &str) -> Option<Box<dyn LayoutStrategy>> {
+    strategies().into_iter().find(|s| s.name() == name)
+}
+
+
+// rows x cols grid, joins squares along a row or merges blocks ------------
+pub struct GridLayout;
+
+impl LayoutStrategy for GridLayout {
+    fn name(&self) -> &'static str {
+        "grid"
+    }
+
+    fn generate(
+        &self,
+        page: Rect,
+        settings: &Settings,
+        shape_rng: &mut ChaCha12Rng,
+        layout_rng: &mut ChaCha12Rng,
+    ) -> Vec<Vec<LayoutItem>> {
+        generate_layout(
+            &GridContext::new(page, settings),
+            &settings.row_sizes,
+            &settings.col_sizes,
+            &settings.block_merge,
+            shape_rng,
+            layout_rng,
+        )
+    }
+
+    fn settings_ui(&self, ui: &mut egui::Ui, settings: &mut Settings) -> bool {
+        let rows_changed = track_sizes_ui(ui, "Row Sizes", &mut settings.row_sizes);
+        let cols_changed = track_sizes_ui(ui, "Col Sizes", &mut settings.col_sizes);
+        block_merge_ui(ui, &mut settings.block_merge) || rows_changed || cols_changed
+    }
+}
+
+
+// hexagon tiling, pointy top, every other row shifted by half a cell --------
+// Cols sets the cell size, as many rows as fit are added.
+pub struct HexLayout;
+
+impl LayoutStrategy for HexLayout {
+    fn name(&self) -> &'static str {
+        "hex"
+    }
+
+    fn generate(
+        &self,
+        page: Rect,
+        settings: &Settings,
+        shape_rng: &mut ChaCha12Rng,
+        layout_rng: &mut ChaCha12Rng,
+    ) -> Vec<Vec<LayoutItem>> {
+        let r = page.pad(settings.page_padding);
+
+        generate_tile_layout(
+            hex_tiles(r, settings.col_total),
+            settings.gap,
+            &settings.shape_weights,
+            &settings.block_merge,
+            shape_rng,
+            layout_rng,
+        )
+    }
+
+    fn settings_ui(&self, ui: &mut egui::Ui, settings: &mut Settings) -> bool {
+        block_merge_ui(ui, &mut settings.block_merge)
+    }
+}
+
+
+// equilateral triangles, alternating up and down ---------------------------
+// Cols is the number of triangles per row, as many rows as fit are added.
+pub struct TriangleLayout;
+
+impl LayoutStrategy for TriangleLayout {
+    fn name(&self) -> &'static str {
+        "triangle"
+    }
+
+    fn generate(
+        &self,
+        page: Rect,
+        settings: &Settings,
+        shape_rng: &mut ChaCha12Rng,
+        layout_rng: &mut ChaCha12Rng,
+    ) -> Vec<Vec<LayoutItem>> {
+        let r = page.pad(settings.page_padding);
+
+        generate_tile_layout(
+            triangle_tiles(r, settings.col_total),
+            settings.gap,
+            &settings.shape_weights,
+            &settings.block_merge,
+            shape_rng,
+            layout_rng,
+        )
+    }
+
+    fn settings_ui(&self, ui: &mut egui::Ui, settings: &mut Settings) -> bool {
+        block_merge_ui(ui, &mut settings.block_merge)
+    }
+}
+
+
+// non overlapping circles of varying size ---------------------------------
+// Rows, cols, gap and merging are not used, the circles set their own spacing.
+pub struct CirclePackingLayout;
+
+impl LayoutStrategy for CirclePackingLayout {
+    fn name(&self) -> &'static str {
+        "circle_packing"
+    }
+
+    fn generate(
+        &self,
+        page: Rect,
+        settings: &Settings,
+        shape_rng: &mut ChaCha12Rng,
+        layout_rng: &mut ChaCha12Rng,
+    ) -> Vec<Vec<LayoutItem>> {
+        generate_packing_layout(
+            page,
+            settings.page_padding,
+            &settings.packing,
+            &settings.shape_weights,
+            shape_rng,
+            layout_rng,
+        )
+    }
+
+    fn settings_ui(&self, ui: &mut egui::Ui, settings: &mut Settings) -> bool {
+        let mut changed = false;
+
+        let packing_sliders = [
+            (&mut settings.packing.min_radius, 0.5..=50.0, "Min Radius (mm)"),
+            (&mut settings.packing.max_radius, 0.5..=150.0, "Max Radius (mm)"),
+            (&mut settings.packing.padding, 0.0..=15.0, "Circle Padding (mm)"),
+        ];
+        for (value, range, text) in packing_sliders {
+            if ui.add(egui::Slider::new(value, range).text(text)).changed() {
+                changed = true;
+            }
+        }
+
+        if ui.add(
+            egui::Slider::new(
+                &mut settings.packing.attempts, 100..=20000,
+            ).text("Attempts")
+        ).changed() {
+            changed = true;
+        }
+
+        changed
+    }
+}
+
+
+// loose scatter, items at least a minimum distance apart -------------------
+// Rows, cols and merging are not used.
+pub struct PoissonLayout;
+
+impl LayoutStrategy for PoissonLayout {
+    fn name(&self) -> &'static str {
+        "poisson"
+    }
+
+    fn generate(
+        &self,
+        page: Rect,
+        settings: &Settings,
+        shape_rng: &mut ChaCha12Rng,
+        layout_rng: &mut ChaCha12Rng,
+    ) -> Vec<Vec<LayoutItem>> {
+        generate_poisson_layout(
+            page,
+            settings.page_padding,
+            &settings.poisson,
+            &settings.shape_weights,
+            shape_rng,
+            layout_rng,
+        )
+    }
+
+    fn settings_ui(&self, ui: &mut egui::Ui, settings: &mut Settings) -> bool {
+        let mut changed = false;
+
+        let poisson_sliders = [
+            (&mut settings.poisson.min_distance, 2.0..=100.0, "Min Distance (mm)"),
+            (&mut settings.poisson.item_size, 1.0..=100.0, "Item Size (mm)"),
+            (&mut settings.poisson.centre_falloff, -1.0..=1.0, "Size Falloff From Centre"),
+        ];
+        for (value, range, text) in poisson_sliders {
+            if ui.add(egui::Slider::new(value, range).text(text)).changed() {
+                changed = true;
+            }
+        }
+
+        if ui.add(
+            egui::Slider::new(
+                &mut settings.poisson.samples, 1..=60,
+            ).text("Samples Per Point")
+        ).changed() {
+            changed = true;
+        }
+
+        changed
+    }
+}
+
+
+// voronoi cells around seeded points, stained glass style ------------------
+pub struct VoronoiLayout;
+
+impl LayoutStrategy for VoronoiLayout {
+    fn name(&self) -> &'static str {
+        "voronoi"
+    }
+
+    fn generate(
+        &self,
+        page: Rect,
+        settings: &Settings,
+        shape_rng: &mut ChaCha12Rng,
+        layout_rng: &mut ChaCha12Rng,
+    ) -> Vec<Vec<LayoutItem>> {
+        generate_tessellation_layout(page, settings, false, shape_rng, layout_rng)
+    }
+
+    fn settings_ui(&self, ui: &mut egui::Ui, settings: &mut Settings) -> bool {
+        let changed = tessellation_ui(ui, &mut settings.tessellation);
+        block_merge_ui(ui, &mut settings.block_merge) || changed
+    }
+}
+
+
+// delaunay triangles between the same seeded points ------------------------
+pub struct DelaunayLayout;
+
+impl LayoutStrategy for DelaunayLayout {
+    fn name(&self) -> &'static str {
+        "delaunay"
+    }
+
+    fn generate(
+        &self,
+        page: Rect,
+        settings: &Settings,
+        shape_rng: &mut ChaCha12Rng,
+        layout_rng: &mut ChaCha12Rng,
+    ) -> Vec<Vec<LayoutItem>> {
+        generate_tessellation_layout(page, settings, true, shape_rng, layout_rng)
+    }
+
+    fn settings_ui(&self, ui: &mut egui::Ui, settings: &mut Settings) -> bool {
+        let changed = tessellation_ui(ui, &mut settings.tessellation);
+        block_merge_ui(ui, &mut settings.block_merge) || changed
+    }
+}
+
+
+// recursive split, cuts snap to the rows / cols grid -----------------------
+pub struct BspLayout;
+
+impl LayoutStrategy for BspLayout {
+    fn name(&self) -> &'static str {
+        "bsp"
+    }
+
+    fn generate(
+        &self,
+        page: Rect,
+        settings: &Settings,
+        shape_rng: &mut ChaCha12Rng,
+        layout_rng: &mut ChaCha12Rng,
+    ) -> Vec<Vec<LayoutItem>> {
+        generate_bsp_layout(
+            &GridContext::new(page, settings),
+            &settings.bsp,
+            shape_rng,
+            layout_rng,
+        )
+    }
+
+    fn settings_ui(&self, ui: &mut egui::Ui, settings: &mut Settings) -> bool {
+        let mut changed = false;
+
+        let bsp_sliders = [
+            (&mut settings.bsp.split_min, 0.1..=0.9, "Split Min"),
+            (&mut settings.bsp.split_max, 0.1..=0.9, "Split Max"),
+            (&mut settings.bsp.min_size, 2.0..=150.0, "Min Block Size (mm)"),
+        ];
+        for (value, range, text) in bsp_sliders {
+            if ui.add(egui::Slider::new(value, range).text(text)).changed() {
+                changed = true;
+            }
+        }
+
+        if ui.add(
+            egui::Slider::new(
+                &mut settings.bsp.max_depth, 0..=12,
+            ).text("Max Depth")
+        ).changed() {
+            changed = true;
+        }
+
+        changed
+    }
+}
+
+
+// the page and rows x cols grid the grid style strategies share, in mm
+struct GridContext<'a> {
+    page: Rect,
+    page_padding: f32,
+    rows: i32,
+    cols: i32,
+    gap: f32,
+    shape_weights: &'a ShapeWeights,
+}
+
+impl<'a> GridContext<'a> {
+    fn new(page: Rect, settings: &'a Settings) -> GridContext<'a> {
+        GridContext {
+            page,
+            page_padding: settings.page_padding,
+            rows: settings.row_total,
+            cols: settings.col_total,
+            gap: settings.gap,
+            shape_weights: &settings.shape_weights,
+        }
+    }
+}
+
+
+fn generate_layout(
+    grid_context: &GridContext,
+    row_sizes: &TrackSizes,
+    col_sizes: &TrackSizes,
+    block_merge: &BlockMerge,
+    shape_rng: &mut ChaCha12Rng,
+    layout_rng: &mut ChaCha12Rng,
+) -> Vec<Vec<LayoutItem>> {
+    let (rows, cols, gap) = (grid_context.rows, grid_context.cols, grid_context.gap);
+    let shape_weights = grid_context.shape_weights;
+
+    let r = Rect::from_xy_wh(
+        grid_context.page.xy(),
+        grid_context.page.wh(),
+    ).pad(grid_context.page_padding);
+
+
+    let mut layout = vec![];
+
+
+    // bottom row / left col first
+    let row_heights = track_sizes(r.h(), rows, row_sizes, layout_rng);
+    let col_widths = track_sizes(r.w(), cols, col_sizes, layout_rng);
+
+    if block_merge.enabled {
+        let mut cells = vec![];
+        for _ in 0..rows {
+            let row_shapes: Vec<Shapes> = (0..cols)
+                .map(|_| get_rnd_shape(shape_rng, shape_weights))
+                .collect();
+            cells.push(row_shapes);
+        }
+
+        return merge_blocks(&cells, r, &row_heights, &col_widths, gap, block_merge, layout_rng);
+    }
+
+    let mut y = r.bottom();
+
+    // generate items ---------------------------------------------------
+    for row_h in row_heights.iter().cloned() {
+        let row_rect = Rect::from_xy_wh(
+            pt2(0.0, 0.0),
+            pt2(r.w(), row_h),
+        );
+
+        let mut x = row_rect.left();
+        let mut row_items = vec![];
+
+        // gen shapes we want
+        for col_w in col_widths.iter().cloned() {
+            row_items.push(
+                LayoutItem::new(
+                    get_rnd_shape(shape_rng, shape_weights),
+                    Rect::from_xy_wh(
+                        pt2(x + col_w / 2.0, y + row_h / 2.0),
+                        pt2(col_w, row_h),
+                    ),
+                )
+            );
+
+            x = x + col_w;
+        }
+
+        let mut new_row_items = vec![];
+        let mut new_layout_item: Option<LayoutItem> = None;
+        //.pad(gap.to_f32().unwrap())
+
+        // join the squares together ------------------------------------------------
+        for x_item in row_items {
+            if x_item.shape == Shapes::Square {
+                if new_layout_item.is_none() {
+                    new_layout_item = Some(
+                        LayoutItem::new(x_item.shape, x_item.dimensions)
+                    );
+                } else {
+                    // we have one already streatch out
+                    let t_item = new_layout_item.unwrap();
+                    let w1 = t_item.dimensions.w();
+                    let r = t_item.dimensions.right();
+                    let new_r = t_item.dimensions
+                        .stretch_to_point(
+                            [
+                                x_item.dimensions.right(),
+                                x_item.dimensions.top(),
+                            ],
+                        );
+
+                    let df = LayoutItem {
+                        cell_count: t_item.cell_count + 1,
+                        ..LayoutItem::new(
+                            x_item.shape,
+                            Rect::from_xy_wh(new_r.xy(), new_r.wh()),
+                        )
+                    };
+
+                    new_layout_item = Some(df);
+                }
+            } else {
+                // where we just working with a square
+
+                if new_layout_item.is_some() {
+                    new_row_items.push(
+                        new_layout_item.unwrap().clone()
+                    );
+                }
+                new_layout_item = None;
+
+                new_layout_item = Some(
+                    LayoutItem::new(x_item.shape, x_item.dimensions)
+                );
+
+                new_row_items.push(
+                    new_layout_item.unwrap().clone()
+                );
+                new_layout_item = None;
+            }
+        }
+        if (new_layout_item.is_some()) {
+            new_row_items.push(
+                new_layout_item.unwrap().clone()
+            );
+        }
+
+
+        // add padding
+        let mut padded_row = vec![];
+        for non_padded in new_row_items {
+            let d = non_padded.dimensions.pad(gap);
+            padded_row.push(
+                LayoutItem {
+                    cell_count: non_padded.cell_count,
+                    ..LayoutItem::new(non_padded.shape, d)
+                }
+            )
+        }
+
+        layout.push(padded_row);
+        y = y + row_h;
+    }
+
+    layout
+}
+
+
+// recursive binary space partition (mondrian style) -----------------------
+// Splits land on the rows / cols grid so the blocks still line up. Returns a
+// single row holding every block.
+fn generate_bsp_layout(
+    grid_context: &GridContext,
+    bsp: &BspSettings,
+    shape_rng: &mut ChaCha12Rng,
+    layout_rng: &mut ChaCha12Rng,
+) -> Vec<Vec<LayoutItem>> {
+    let (rows, cols, gap) = (grid_context.rows, grid_context.cols, grid_context.gap);
+
+    let r = Rect::from_xy_wh(
+        grid_context.page.xy(),
+        grid_context.page.wh(),
+    ).pad(grid_context.page_padding);
+
+    let grid = pt2(
+        r.w() / cols.max(1).to_f32().unwrap(),
+        r.h() / rows.max(1).to_f32().unwrap(),
+    );
+
+    let mut blocks = vec![];
+    bsp_split(r, r, grid, 0, bsp, layout_rng, &mut blocks);
+
+    let items = blocks.iter()
+        .map(|block| {
+            LayoutItem::new(
+                get_rnd_shape(shape_rng, grid_context.shape_weights),
+                block.pad(gap),
+            )
+        })
+        .collect();
+
+    vec![items]
+}
+
+
+fn bsp_split(
+    rect: Rect,
+    page: Rect,
+    grid: Point2,
+    depth: u32,
+    bsp: &BspSettings,
+    rng: &mut ChaCha12Rng,
+    blocks: &mut Vec<Rect>,
+) {
+    let can_split_x = rect.w() >= bsp.min_size * 2.0;
+    let can_split_y = rect.h() >= bsp.min_size * 2.0;
+
+    if depth >= bsp.max_depth || (!can_split_x && !can_split_y) {
+        blocks.push(rect);
+        return;
+    }
+
+    // favour cutting across the long side so blocks don't get too thin
+    let split_x = if can_split_x && can_split_y {
+        rng.gen::<f32>() < rect.w() / (rect.w() + rect.h())
+    } else {
+        can_split_x
+    };
+
+    let ratio = rng.gen_range(bsp.split_min..=bsp.split_max.max(bsp.split_min));
+
+    if split_x {
+        let x = snap(rect.left() + rect.w() * ratio, page.left(), grid.x);
+        if x - rect.left() < bsp.min_size || rect.right() - x < bsp.min_size {
+            blocks.push(rect);
+            return;
+        }
+
+        let left = Rect::from_corners(rect.bottom_left(), pt2(x, rect.top()));
+        let right = Rect::from_corners(pt2(x, rect.bottom()), rect.top_right());
+        bsp_split(left, page, grid, depth + 1, bsp, rng, blocks);
+        bsp_split(right, page, grid, depth + 1, bsp, rng, blocks);
+    } else {
+        let y = snap(rect.bottom() + rect.h() * ratio, page.bottom(), grid.y);
+        if y - rect.bottom() < bsp.min_size || rect.top() - y < bsp.min_size {
+            blocks.push(rect);
+            return;
+        }
+
+        let bottom = Rect::from_corners(rect.bottom_left(), pt2(rect.right(), y));
+        let top = Rect::from_corners(pt2(rect.left(), y), rect.top_right());
+        bsp_split(bottom, page, grid, depth + 1, bsp, rng, blocks);
+        bsp_split(top, page, grid, depth + 1, bsp, rng, blocks);
+    }
+}
+
+
+// pack circles by trying random centres ------------------------------------
+// Each spot gets a random radius, shrunk to whatever room is left there.
+// Circle shapes take the whole packed circle, anything else is drawn in the
+// square inscribed in it so it can't poke into a neighbour.
+fn generate_packing_layout(
+    win_rect: Rect,
+    page_padding: f32,
+    packing: &PackingSettings,
+    shape_weights: &ShapeWeights,
+    shape_rng: &mut ChaCha12Rng,
+    layout_rng: &mut ChaCha12Rng,
+) -> Vec<Vec<LayoutItem>> {
+    let r = win_rect.pad(page_padding);
+
+    let min_radius = packing.min_radius.max(0.5);
+    let max_radius = packing.max_radius.max(min_radius);
+
+    let mut circles: Vec<(Point2, f32)> = vec![];
+
+    for _ in 0..packing.attempts {
+        let centre = pt2(
+            layout_rng.gen_range(r.left()..=r.right()),
+            layout_rng.gen_range(r.bottom()..=r.top()),
+        );
+        let target = layout_rng.gen_range(min_radius..=max_radius);
+
+        // room to the page edge, then to every circle placed so far
+        let mut room = (centre.x - r.left())
+            .min(r.right() - centre.x)
+            .min(centre.y - r.bottom())
+            .min(r.top() - centre.y);
+
+        for (other, other_radius) in circles.iter() {
+            room = room.min(centre.distance(*other) - other_radius - packing.padding);
+        }
+
+        if room < min_radius {
+            continue;
+        }
+
+        circles.push((centre, target.min(room)));
+    }
+
+    let items = circles.iter()
+        .map(|(centre, radius)| {
+            let shape = get_rnd_shape(shape_rng, shape_weights);
+            let size = if shape == Shapes::Circle {
+                radius * 2.0
+            } else {
+                radius * 2.0_f32.sqrt()
+            };
+
+            LayoutItem::new(shape, Rect::from_xy_wh(*centre, pt2(size, size)))
+        })
+        .collect();
+
+    vec![items]
+}
+
+
+// poisson disc scatter, items shrink / grow with distance from the centre --
+fn generate_poisson_layout(
+    win_rect: Rect,
+    page_padding: f32,
+    poisson: &PoissonSettings,
+    shape_weights: &ShapeWeights,
+    shape_rng: &mut ChaCha12Rng,
+    layout_rng: &mut ChaCha12Rng,
+) -> Vec<Vec<LayoutItem>> {
+    // keep the centres far enough in that the biggest item still fits
+    let r = win_rect
+        .pad(page_padding)
+        .pad(poisson.item_size.max(1.0) / 2.0);
+    let min_distance = poisson.min_distance.max(1.0);
+
+    if r.w() <= 0.0 || r.h() <= 0.0 {
+        return vec![vec![]];
+    }
+
+    let points = poisson_points(r, min_distance, poisson.samples, layout_rng);
+
+    // 0 in the middle of the page, 1 in the corners
+    let max_dist = (r.w() * r.w() + r.h() * r.h()).sqrt() / 2.0;
+
+    let items = points.iter()
+        .map(|p| {
+            let t = p.distance(r.xy()) / max_dist;
+            let scale = if poisson.centre_falloff >= 0.0 {
+                1.0 - poisson.centre_falloff * t
+            } else {
+                1.0 + poisson.centre_falloff * (1.0 - t)
+            };
+            let size = (poisson.item_size * scale).max(1.0);
+
+            LayoutItem::new(
+                get_rnd_shape(shape_rng, shape_weights),
+                Rect::from_xy_wh(*p, pt2(size, size)),
+            )
+        })
+        .collect();
+
+    vec![items]
+}
+
+
+// bridson's poisson disc sampling -------------------------------------------
+// A background grid with cells of min_distance / sqrt(2) holds at most one
+// point each, so only the 5x5 cells around a candidate need checking.
+fn poisson_points(r: Rect, min_distance: f32, samples: u32, rng: &mut ChaCha12Rng) -> Vec<Point2> {
+    let cell = min_distance / 2.0_f32.sqrt();
+    let grid_w = (r.w() / cell).ceil().max(1.0) as usize;
+    let grid_h = (r.h() / cell).ceil().max(1.0) as usize;
+    let mut grid: Vec<Option<usize>> = vec![None; grid_w * grid_h];
+
+    let grid_index = |p: Point2| {
+        let gx = (((p.x - r.left()) / cell) as usize).min(grid_w - 1);
+        let gy = (((p.y - r.bottom()) / cell) as usize).min(grid_h - 1);
+        (gx, gy)
+    };
+
+    let mut points: Vec<Point2> = vec![];
+    let mut active: Vec<usize> = vec![];
+
+    let first = pt2(
+        rng.gen_range(r.left()..=r.right()),
+        rng.gen_range(r.bottom()..=r.top()),
+    );
+    let (gx, gy) = grid_index(first);
+    grid[gy * grid_w + gx] = Some(0);
+    points.push(first);
+    active.push(0);
+
+    while !active.is_empty() {
+        let a = carbon_random::pick_index(rng, active.len());
+        let origin = points[active[a]];
+        let mut found = false;
+
+        for _ in 0..samples.max(1) {
+            // somewhere in the ring between min_distance and twice that
+            let angle = rng.gen_range(0.0..std::f32::consts::TAU);
+            let dist = rng.gen_range(min_distance..min_distance * 2.0);
+            let candidate = origin + pt2(angle.cos(), angle.sin()) * dist;
+
+            if !r.contains(candidate) {
+                continue;
+            }
+
+            let (cx, cy) = grid_index(candidate);
+            let mut too_close = false;
+
+            for ny in cy.saturating_sub(2)..(cy + 3).min(grid_h) {
+                for nx in cx.saturating_sub(2)..(cx + 3).min(grid_w) {
+                    if let Some(other) = grid[ny * grid_w + nx] {
+                        if points[other].distance(candidate) < min_distance {
+                            too_close = true;
+                        }
+                    }
+                }
+            }
+
+            if !too_close {
+                grid[cy * grid_w + cx] = Some(points.len());
+                active.push(points.len());
+                points.push(candidate);
+                found = true;
+                break;
+            }
+        }
+
+        if !found {
+            active.swap_remove(a);
+        }
+    }
+
+    points
+}
+
+
+// nearest grid line, measured from `origin`
+fn snap(value: f32, origin: f32, step: f32) -> f32 {
+    if step <= 0.0 {
+        return value;
+    }
+    origin + ((value - origin) / step).round() * step
+}
+
+
+// split `total` into `count` tracks -----------------------------------------
+pub fn track_sizes(total: f32, count: i32, tracks: &TrackSizes, rng: &mut ChaCha12Rng) -> Vec<f32> {
+    let count = count.max(0) as usize;
+
+    let mut weights: Vec<f32> = match tracks.mode {
+        TrackMode::Uniform => vec![1.0; count],
+
+        TrackMode::Fibonacci => {
+            let mut fib = vec![1.0_f32, 1.0];
+            while fib.len() < count {
+                let next = fib[fib.len() - 1] + fib[fib.len() - 2];
+                fib.push(next);
+            }
+            fib.truncate(count);
+            fib
+        }
+
+        TrackMode::Golden => {
+            let phi = (1.0 + 5.0_f32.sqrt()) / 2.0;
+            (0..count).map(|i| phi.powi(i as i32)).collect()
+        }
+
+        TrackMode::Geometric => (0..count)
+            .map(|i| tracks.ratio.max(0.01).powi(i as i32))
+            .collect(),
+
+        TrackMode::Random => (0..count)
+            .map(|_| rng.gen_range(0.2..1.0))
+            .collect(),
+
+        TrackMode::Explicit => {
+            let list = parse_track_list(&tracks.list);
+            if list.is_empty() {
+                vec![1.0; count]
+            } else {
+                (0..count).map(|i| list[i % list.len()]).collect()
+            }
+        }
+    };
+
+    if tracks.reverse {
+        weights.reverse();
+    }
+
+    let sum: f32 = weights.iter().sum();
+    if sum <= 0.0 {
+        return vec![total / count.max(1) as f32; count];
+    }
+
+    weights.iter().map(|w| total * w / sum).collect()
+}
+
+// "1, 2, 3" or "1 2 3", anything that isn't a positive number is skipped
+pub fn parse_track_list(text: &str) -> Vec<f32> {
+    text.split(|c: char| c == ',' || c.is_whitespace())
+        .filter_map(|s| s.trim().parse::<f32>().ok())
+        .filter(|w| *w > 0.0)
+        .collect()
+}
+
+// where each track starts, plus the far end of the last one
+fn track_edges(start: f32, sizes: &[f32]) -> Vec<f32> {
+    let mut edges = vec![start];
+    for size in sizes.iter() {
+        edges.push(edges[edges.len() - 1] + size);
+    }
+    edges
+}
+
+
+fn track_sizes_ui(ui: &mut egui::Ui, label: &str, tracks: &mut TrackSizes) -> bool {
+    let mut changed = false;
+
+    let mode = tracks.mode;
+    egui::ComboBox::from_label(label)
+        .selected_text(format!("{:?}", tracks.mode))
+        .show_ui(ui, |ui| {
+            ui.selectable_value(&mut tracks.mode, TrackMode::Uniform, "Uniform");
+            ui.selectable_value(&mut tracks.mode, TrackMode::Fibonacci, "Fibonacci");
+            ui.selectable_value(&mut tracks.mode, TrackMode::Golden, "Golden");
+            ui.selectable_value(&mut tracks.mode, TrackMode::Geometric, "Geometric");
+            ui.selectable_value(&mut tracks.mode, TrackMode::Random, "Random");
+            ui.selectable_value(&mut tracks.mode, TrackMode::Explicit, "Explicit");
+        });
+    if mode != tracks.mode {
+        changed = true;
+    }
+
+    match tracks.mode {
+        TrackMode::Geometric => {
+            if ui.add(
+                egui::Slider::new(
+                    &mut tracks.ratio, 0.5..=2.0,
+                ).text(format!("{} Ratio", label))
+            ).changed() {
+                changed = true;
+            }
+        }
+        TrackMode::Explicit => {
+            if ui.text_edit_singleline(&mut tracks.list).changed() {
+                changed = true;
+            }
+        }
+        _ => {}
+    }
+
+    if tracks.mode != TrackMode::Uniform
+        && ui.add(egui::Checkbox::new(&mut tracks.reverse, format!("Reverse {}", label))).changed() {
+        changed = true;
+    }
+
+    changed
+}
+
+
+// join same shape cells into blocks over several rows and cols ------------
+// cells[0] is the bottom row. Each block is added to the row it starts in.
+pub fn merge_blocks(
+    cells: &[Vec<Shapes>],
+    r: Rect,
+    row_heights: &[f32],
+    col_widths: &[f32],
+    gap: f32,
+    block_merge: &BlockMerge,
+    layout_rng: &mut ChaCha12Rng,
+) -> Vec<Vec<LayoutItem>> {
+    let rows = cells.len();
+    let cols = if rows > 0 { cells[0].len() } else { 0 };
+    let row_edges = track_edges(r.bottom(), row_heights);
+    let col_edges = track_edges(r.left(), col_widths);
+    let mut claimed = vec![vec![false; cols]; rows];
+    let mut layout = vec![];
+
+    for row in 0..rows {
+        let mut row_items = vec![];
+
+        for col in 0..cols {
+            if claimed[row][col] {
+                continue;
+            }
+
+            let shape = &cells[row][col];
+            let mut block_w = 1;
+            let mut block_h = 1;
+
+            if layout_rng.gen::<f32>() < block_merge.chance {
+                // aim for a random block size, then shrink to what actually fits
+                let target_w = layout_rng.gen_range(1..=block_merge.max_cols.max(1)) as usize;
+                let target_h = layout_rng.gen_range(1..=block_merge.max_rows.max(1)) as usize;
+
+                while block_w < target_w
+                    && col + block_w < cols
+                    && !claimed[row][col + block_w]
+                    && &cells[row][col + block_w] == shape {
+                    block_w += 1;
+                }
+
+                while block_h < target_h
+                    && row + block_h < rows
+                    && (col..col + block_w).all(|c| {
+                        !claimed[row + block_h][c] && &cells[row + block_h][c] == shape
+                    }) {
+                    block_h += 1;
+                }
+            }
+
+            for claim_row in claimed.iter_mut().skip(row).take(block_h) {
+                claim_row[col..col + block_w].fill(true);
+            }
+
+            let block = Rect::from_corners(
+                pt2(col_edges[col], row_edges[row]),
+                pt2(col_edges[col + block_w], row_edges[row + block_h]),
+            );
+
+            row_items.push(
+                LayoutItem {
+                    cell_count: (block_w * block_h) as u32,
+                    ..LayoutItem::new(shape.clone(), block.pad(gap))
+                }
+            );
+        }
+
+        layout.push(row_items);
+    }
+
+    layout
+}
+
+
+// the tilings have no cols / rows to count, so a merged group there grows to
+// at most max cols x max rows tiles
+fn block_merge_ui(ui: &mut egui::Ui, block_merge: &mut BlockMerge) -> bool {
+    let mut changed = false;
+
+    if ui.add(egui::Checkbox::new(&mut block_merge.enabled, "Merge Blocks")).changed() {
+        changed = true;
+    }
+
+    if ui.add(
+        egui::Slider::new(
+            &mut block_merge.chance, 0.0..=1.0,
+        ).text("Merge Chance")
+    ).changed() {
+        changed = true;
+    }
+
+    if ui.add(
+        egui::Slider::new(
+            &mut block_merge.max_cols, 1..=10,
+        ).text("Max Block Cols")
+    ).changed() {
+        changed = true;
+    }
+
+    if ui.add(
+        egui::Slider::new(
+            &mut block_merge.max_rows, 1..=10,
+        ).text("Max Block Rows")
+    ).changed() {
+        changed = true;
+    }
+
+    changed
+}
+
+
+fn tessellation_ui(ui: &mut egui::Ui, tessellation: &mut TessellationSettings) -> bool {
+    let mut changed = false;
+
+    let seeding = tessellation.seeding;
+    egui::ComboBox::from_label("Points")
+        .selected_text(format!("{:?}", tessellation.seeding))
+        .show_ui(ui, |ui| {
+            ui.selectable_value(&mut tessellation.seeding, PointSeeding::Random, "Random");
+            ui.selectable_value(&mut tessellation.seeding, PointSeeding::Poisson, "Poisson");
+            ui.selectable_value(&mut tessellation.seeding, PointSeeding::JitteredGrid, "Jittered Grid");
+        });
+    if seeding != tessellation.seeding {
+        changed = true;
+    }
+
+    match tessellation.seeding {
+        PointSeeding::Random => {
+            if ui.add(
+                egui::Slider::new(
+                    &mut tessellation.point_count, 3..=600,
+                ).text("Point Count")
+            ).changed() {
+                changed = true;
+            }
+        }
+        PointSeeding::Poisson => {
+            if ui.add(
+                egui::Slider::new(
+                    &mut tessellation.spacing, 5.0..=100.0,
+                ).text("Point Spacing (mm)")
+            ).changed() {
+                changed = true;
+            }
+        }
+        PointSeeding::JitteredGrid => {
+            if ui.add(
+                egui::Slider::new(
+                    &mut tessellation.jitter, 0.0..=1.0,
+                ).text("Jitter")
+            ).changed() {
+                changed = true;
+            }
+        }
+    }
+
+    if ui.add(
+        egui::Slider::new(
+            &mut tessellation.relax_iterations, 0..=20,
+        ).text("Relax Iterations")
+    ).changed() {
+        changed = true;
+    }
+
+    changed
+}
+
+
+// rows of hexagon polygons, centred on the page
+fn hex_tiles(r: Rect, cols: i32) -> Vec<Vec<Vec<Point2>>> {
+    let cols = cols.max(1);
+    let cell_w = r.w() / (cols.to_f32().unwrap() + 0.5);
+    let radius = cell_w / 3.0_f32.sqrt();
+    let step_y = radius * 1.5;
+
+    if r.h() < radius * 2.0 {
+        return vec![];
+    }
+
+    let rows = ((r.h() - radius * 2.0) / step_y).floor() as i32 + 1;
+    let total_h = radius * 2.0 + (rows - 1).to_f32().unwrap() * step_y;
+    let start_y = r.bottom() + (r.h() - total_h) / 2.0 + radius;
+
+    (0..rows)
+        .map(|row| {
+            let y = start_y + row.to_f32().unwrap() * step_y;
+            let offset = if row % 2 == 1 { cell_w / 2.0 } else { 0.0 };
+
+            (0..cols)
+                .map(|col| {
+                    let x = r.left() + cell_w / 2.0 + col.to_f32().unwrap() * cell_w + offset;
+                    (0..6)
+                        .map(|i| {
+                            let a = (30.0 + 60.0 * i.to_f32().unwrap()).to_radians();
+                            pt2(x + a.cos() * radius, y + a.sin() * radius)
+                        })
+                        .collect()
+                })
+                .collect()
+        })
+        .collect()
+}
+
+
+// rows of triangle polygons, centred on the page
+fn triangle_tiles(r: Rect, cols: i32) -> Vec<Vec<Vec<Point2>>> {
+    let cols = cols.max(1);
+    let side = r.w() * 2.0 / (cols.to_f32().unwrap() + 1.0);
+    let tri_h = side * 3.0_f32.sqrt() / 2.0;
+    let rows = (r.h() / tri_h).floor() as i32;
+    let start_y = r.bottom() + (r.h() - rows.to_f32().unwrap() * tri_h) / 2.0;
+
+    (0..rows)
+        .map(|row| {
+            let b = start_y + row.to_f32().unwrap() * tri_h;
+            let t = b + tri_h;
+
+            (0..cols)
+                .map(|col| {
+                    let x = r.left() + col.to_f32().unwrap() * side / 2.0;
+                    if (row + col) % 2 == 0 {
+                        vec![pt2(x, b), pt2(x + side, b), pt2(x + side / 2.0, t)]
+                    } else {
+                        vec![pt2(x + side / 2.0, b), pt2(x + side, t), pt2(x, t)]
+                    }
+                })
+                .collect()
+        })
+        .collect()
+}
+
+
+// voronoi cells or delaunay triangles over the padded page ------------------
+fn generate_tessellation_layout(
+    win_rect: Rect,
+    settings: &Settings,
+    delaunay: bool,
+    shape_rng: &mut ChaCha12Rng,
+    layout_rng: &mut ChaCha12Rng,
+) -> Vec<Vec<LayoutItem>> {
+    let r = win_rect.pad(settings.page_padding);
+    let tessellation = &settings.tessellation;
+
+    let sites = tessellation_sites(r, settings, layout_rng);
+    let sites = carbon_tessellation::lloyd_relax(&sites, r, tessellation.relax_iterations);
+
+    let cells: Vec<Vec<Point2>> = if delaunay {
+        // points around the page edge, about as far apart as the sites, make
+        // the triangles cover the whole page without long slivers at the sides
+        let step = (r.w() * r.h() / sites.len().max(1) as f32).sqrt();
+        let mut points = sites;
+        for (a, b) in [
+            (r.bottom_left(), r.bottom_right()),
+            (r.bottom_right(), r.top_right()),
+            (r.top_right(), r.top_left()),
+            (r.top_left(), r.bottom_left()),
+        ] {
+            let steps = (a.distance(b) / step).ceil().max(1.0) as u32;
+            for i in 0..steps {
+                points.push(a.lerp(b, i as f32 / steps as f32));
+            }
+        }
+
+        carbon_tessellation::delaunay_triangles(&points)
+            .iter()
+            .map(|t| vec![points[t[0]], points[t[1]], points[t[2]]])
+            .collect()
+    } else {
+        carbon_tessellation::voronoi_cells(&sites, r)
+    };
+
+    // thin cells are kept too, generate_tile_layout insets them by less than the gap
+    let cells = cells.into_iter()
+        .filter(|cell| cell.len() >= 3)
+        .collect();
+
+    generate_tile_layout(
+        vec![cells],
+        settings.gap,
+        &settings.shape_weights,
+        &settings.block_merge,
+        shape_rng,
+        layout_rng,
+    )
+}
+
+
+fn tessellation_sites(r: Rect, settings: &Settings, rng: &mut ChaCha12Rng) -> Vec<Point2> {
+    let tessellation = &settings.tessellation;
+
+    match tessellation.seeding {
+        PointSeeding::Random => (0..tessellation.point_count.max(1))
+            .map(|_| {
+                pt2(
+                    rng.gen_range(r.left()..=r.right()),
+                    rng.gen_range(r.bottom()..=r.top()),
+                )
+            })
+            .collect(),
+
+        PointSeeding::Poisson => poisson_points(r, tessellation.spacing.max(1.0), 30, rng),
+
+        // one site per rows x cols cell, pushed around inside it
+        PointSeeding::JitteredGrid => {
+            let rows = settings.row_total.max(1);
+            let cols = settings.col_total.max(1);
+            let row_h = r.h() / rows.to_f32().unwrap();
+            let col_w = r.w() / cols.to_f32().unwrap();
+            let mut sites = vec![];
+
+            for row in 0..rows {
+                for col in 0..cols {
+                    let jx = rng.gen_range(-0.5..0.5) * tessellation.jitter;
+                    let jy = rng.gen_range(-0.5..0.5) * tessellation.jitter;
+                    sites.push(pt2(
+                        r.left() + (col.to_f32().unwrap() + 0.5 + jx) * col_w,
+                        r.bottom() + (row.to_f32().unwrap() + 0.5 + jy) * row_h,
+                    ));
+                }
+            }
+
+            sites
+        }
+    }
+}
+
+
+// turn rows of tile polygons into layout items ------------------------------
+// Merging grows a group of neighbouring tiles with the same shape, one random
+// neighbour at a time, as long as the outline stays a single loop.
+fn generate_tile_layout(
+    tiles: Vec<Vec<Vec<Point2>>>,
+    gap: f32,
+    shape_weights: &ShapeWeights,
+    block_merge: &BlockMerge,
+    shape_rng: &mut ChaCha12Rng,
+    layout_rng: &mut ChaCha12Rng,
+) -> Vec<Vec<LayoutItem>> {
+
+    let mut polys = vec![];
+    let mut tile_rows = vec![];
+    for (row, row_tiles) in tiles.into_iter().enumerate() {
+        for poly in row_tiles {
+            polys.push(poly);
+            tile_rows.push(row);
+        }
+    }
+
+    let shapes: Vec<Shapes> = polys.iter()
+        .map(|_| get_rnd_shape(shape_rng, shape_weights))
+        .collect();
+
+    let neighbours: Vec<Vec<usize>> = (0..polys.len())
+        .map(|i| {
+            (0..polys.len())
+                .filter(|j| *j != i && carbon_polygon::shares_edge(&polys[i], &polys[*j]))
+                .collect()
+        })
+        .collect();
+
+    let row_count = tile_rows.last().map(|r| r + 1).unwrap_or(0);
+    let mut layout: Vec<Vec<LayoutItem>> = vec![vec![]; row_count];
+    let mut claimed = vec![false; polys.len()];
+
+    for start in 0..polys.len() {
+        if claimed[start] {
+            continue;
+        }
+        claimed[start] = true;
+
+        let mut group = vec![start];
+        let mut outline = polys[start].clone();
+
+        if block_merge.enabled && layout_rng.gen::<f32>() < block_merge.chance {
+            let max_tiles = (block_merge.max_cols.max(1) * block_merge.max_rows.max(1)) as usize;
+            let target = layout_rng.gen_range(1..=max_tiles as u32) as usize;
+            let mut rejected = vec![];
+
+            while group.len() < target {
+                let candidates: Vec<usize> = group.iter()
+                    .flat_map(|g| neighbours[*g].iter().cloned())
+                    .filter(|n| !claimed[*n] && !rejected.contains(n) && shapes[*n] == shapes[start])
+                    .collect();
+
+                if candidates.is_empty() {
+                    break;
+                }
+
+                let pick = candidates[carbon_random::pick_index(layout_rng, candidates.len())];
+                let mut grown: Vec<Vec<Point2>> = group.iter().map(|g| polys[*g].clone()).collect();
+                grown.push(polys[pick].clone());
+
+                match carbon_polygon::merge_outline(&grown) {
+                    Some(merged) => {
+                        outline = merged;
+                        group.push(pick);
+                        claimed[pick] = true;
+                    }
+                    None => rejected.push(pick),
+                }
+            }
+        }
+
+        // shapes go in the circle inside each tile, stretched over the group
+        let mut fit = carbon_polygon::fit_rect(&polys[start]);
+        for g in group.iter().skip(1) {
+            let other = carbon_polygon::fit_rect(&polys[*g]);
+            fit = Rect::from_corners(
+                fit.bottom_left().min(other.bottom_left()),
+                fit.top_right().max(other.top_right()),
+            );
+        }
+
+        // tiles thinner than the gap (tessellation slivers) only give up half
+        // of their inner circle, a full inset would turn them inside out
+        let tile_gap = group.iter()
+            .map(|g| carbon_polygon::edge_distance(&polys[*g], carbon_polygon::centroid(&polys[*g])) / 2.0)
+            .fold(gap, f32::min)
+            .max(0.0);
+
+        carbon_polygon::make_ccw(&mut outline);
+
+        layout[tile_rows[start]].push(
+            LayoutItem {
+                cell: Some(carbon_polygon::inset(&outline, tile_gap)),
+                cell_count: group.len() as u32,
+                ..LayoutItem::new(shapes[start].clone(), fit.pad(tile_gap))
+            }
+        );
+    }
+
+    layout
+}
+
+
+fn get_rnd_shape(rng: &mut ChaCha12Rng, weights: &ShapeWeights) -> Shapes {
+    weights.pick(rng)
+}
@@ -1,32 +1,557 @@
 use nannou::{App, Frame, wgpu};
-use nannou::geom::Rect;
+use nannou::geom::{Point2, Rect};
 use nannou::prelude::ToPrimitive;
 use nannou_egui::Egui;
-use roughr::core::Op;
+use roughr::core::{FillStyle, Op, Options, OptionsBuilder};
+use roughr::Srgba;
+use rand_chacha::ChaCha12Rng;
+use serde::{Deserialize, Serialize};
 
-use crate::carbon;
+use crate::sketch_history::{History, HISTORY_LIMIT};
+use crate::sketch_scene::Scene;
+use crate::carbon::carbon_random;
+use crate::carbon::carbon_serde;
+use crate::carbon::carbon_units;
 
-#[derive(PartialEq, Clone)]
+#[derive(PartialEq, Clone, Debug, Serialize, Deserialize)]
 pub enum Shapes {
     Square,
     Circle,
     Triangle,
+    Hexagon,
+    Diamond,
+    Star { points: u32 },
+    Semicircle,
+    QuarterCircle,
+    Arc,
+    Cross,
+    RoundedRect,
+    Pill,
+    NGon { sides: u32 },
+    // a deliberately blank cell, nothing gets drawn
+    Empty,
     Unset,
 }
 
+impl Shapes {
+    // short name without the parameters, for reports
+    pub fn name(&self) -> &'static str {
+        match self {
+            Shapes::Square => "square",
+            Shapes::Circle => "circle",
+            Shapes::Triangle => "triangle",
+            Shapes::Hexagon => "hexagon",
+            Shapes::Diamond => "diamond",
+            Shapes::Star { .. } => "star",
+            Shapes::Semicircle => "semicircle",
+            Shapes::QuarterCircle => "quarter_circle",
+            Shapes::Arc => "arc",
+            Shapes::Cross => "cross",
+            Shapes::RoundedRect => "rounded_rect",
+            Shapes::Pill => "pill",
+            Shapes::NGon { .. } => "ngon",
+            Shapes::Empty => "empty",
+            Shapes::Unset => "unset",
+        }
+    }
+}
+
+// relative chance of each shape when filling the layout, plus the parameters
+// used for the shapes that take them
+#[derive(PartialEq, Clone, Serialize, Deserialize)]
+pub struct ShapeWeights {
+    pub square: f32,
+    pub circle: f32,
+    pub triangle: f32,
+    pub hexagon: f32,
+    pub diamond: f32,
+    pub star: f32,
+    pub semicircle: f32,
+    pub quarter_circle: f32,
+    pub arc: f32,
+    pub cross: f32,
+    pub rounded_rect: f32,
+    pub pill: f32,
+    pub ngon: f32,
+    pub empty: f32,
+
+    pub star_points: u32,
+    pub ngon_sides: u32,
+}
+
+impl ShapeWeights {
+    pub fn entries(&self) -> [(Shapes, f32); 14] {
+        [
+            (Shapes::Square, self.square),
+            (Shapes::Circle, self.circle),
+            (Shapes::Triangle, self.triangle),
+            (Shapes::Hexagon, self.hexagon),
+            (Shapes::Diamond, self.diamond),
+            (Shapes::Star { points: self.star_points }, self.star),
+            (Shapes::Semicircle, self.semicircle),
+            (Shapes::QuarterCircle, self.quarter_circle),
+            (Shapes::Arc, self.arc),
+            (Shapes::Cross, self.cross),
+            (Shapes::RoundedRect, self.rounded_rect),
+            (Shapes::Pill, self.pill),
+            (Shapes::NGon { sides: self.ngon_sides }, self.ngon),
+            (Shapes::Empty, self.empty),
+        ]
+    }
+
+    pub fn entries_mut(&mut self) -> [(&'static str, &mut f32); 14] {
+        [
+            ("Square", &mut self.square),
+            ("Circle", &mut self.circle),
+            ("Triangle", &mut self.triangle),
+            ("Hexagon", &mut self.hexagon),
+            ("Diamond", &mut self.diamond),
+            ("Star", &mut self.star),
+            ("Semicircle", &mut self.semicircle),
+            ("Quarter Circle", &mut self.quarter_circle),
+            ("Arc", &mut self.arc),
+            ("Cross", &mut self.cross),
+            ("Rounded Rect", &mut self.rounded_rect),
+            ("Pill", &mut self.pill),
+            ("N-Gon", &mut self.ngon),
+            ("Empty", &mut self.empty),
+        ]
+    }
+
+    pub fn pick(&self, rng: &mut ChaCha12Rng) -> Shapes {
+        let entries = self.entries();
+        let weights: Vec<f32> = entries.iter().map(|(_, w)| *w).collect();
+
+        match carbon_random::pick_weighted(rng, &weights) {
+            Some(i) => entries[i].0.clone(),
+            None => Shapes::Empty,
+        }
+    }
+}
+
+// paper the piece is made for, portrait sizes in millimetres
+#[derive(PartialEq, Clone, Copy, Debug, Serialize, Deserialize)]
+pub enum PageSize {
+    A5,
+    A4,
+    A3,
+    A2,
+    Letter,
+    Tabloid,
+    Square,
+    FourByFive,
+    SixteenByNine,
+    Custom,
+}
+
+impl PageSize {
+    pub fn all() -> [PageSize; 10] {
+        [
+            PageSize::A5,
+            PageSize::A4,
+            PageSize::A3,
+            PageSize::A2,
+            PageSize::Letter,
+            PageSize::Tabloid,
+            PageSize::Square,
+            PageSize::FourByFive,
+            PageSize::SixteenByNine,
+            PageSize::Custom,
+        ]
+    }
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            PageSize::A5 => "A5",
+            PageSize::A4 => "A4",
+            PageSize::A3 => "A3",
+            PageSize::A2 => "A2",
+            PageSize::Letter => "Letter",
+            PageSize::Tabloid => "Tabloid",
+            PageSize::Square => "Square",
+            PageSize::FourByFive => "4:5",
+            PageSize::SixteenByNine => "16:9",
+            PageSize::Custom => "Custom",
+        }
+    }
+
+    // None for custom, the page settings hold that size
+    pub fn portrait_mm(&self) -> Option<(f32, f32)> {
+        match self {
+            PageSize::A5 => Some((148.0, 210.0)),
+            PageSize::A4 => Some((210.0, 297.0)),
+            PageSize::A3 => Some((297.0, 420.0)),
+            PageSize::A2 => Some((420.0, 594.0)),
+            PageSize::Letter => Some((215.9, 279.4)),
+            PageSize::Tabloid => Some((279.4, 431.8)),
+            PageSize::Square => Some((300.0, 300.0)),
+            PageSize::FourByFive => Some((240.0, 300.0)),
+            PageSize::SixteenByNine => Some((225.0, 400.0)),
+            PageSize::Custom => None,
+        }
+    }
+}
+
+#[derive(PartialEq, Clone, Copy, Debug, Serialize, Deserialize)]
+pub enum Orientation {
+    Portrait,
+    Landscape,
+}
+
+#[derive(PartialEq, Clone, Serialize, Deserialize)]
+pub struct PageSettings {
+    pub size: PageSize,
+    pub orientation: Orientation,
+    // millimetres, used as is for custom pages (no orientation swap)
+    pub custom_width: f32,
+    pub custom_height: f32,
+    // raster exports only, the svg carries real millimetres
+    pub dpi: f32,
+}
+
+impl PageSettings {
+    pub fn size_mm(&self) -> (f32, f32) {
+        match self.size.portrait_mm() {
+            None => (self.custom_width.max(1.0), self.custom_height.max(1.0)),
+            Some((w, h)) => match self.orientation {
+                Orientation::Portrait => (w, h),
+                Orientation::Landscape => (h, w),
+            },
+        }
+    }
+
+    // the space layouts are made in, centred on the origin
+    pub fn rect_mm(&self) -> Rect {
+        let (w, h) = self.size_mm();
+        Rect::from_w_h(w, h)
+    }
+
+    // the space the scene and svg are drawn in, css pixels
+    pub fn rect_px(&self) -> Rect {
+        let (w, h) = self.size_mm();
+        Rect::from_w_h(
+            carbon_units::mm_to_px(w, carbon_units::CSS_DPI),
+            carbon_units::mm_to_px(h, carbon_units::CSS_DPI),
+        )
+    }
+
+    // size of a raster export at the page dpi
+    pub fn print_pixels(&self) -> (u32, u32) {
+        let (w, h) = self.size_mm();
+        (
+            carbon_units::mm_to_px(w, self.dpi).round() as u32,
+            carbon_units::mm_to_px(h, self.dpi).round() as u32,
+        )
+    }
+}
+
+// how the page is shared out between the rows or the columns
+#[derive(PartialEq, Clone, Copy, Debug, Serialize, Deserialize)]
+pub enum TrackMode {
+    Uniform,
+    Fibonacci,
+    Golden,
+    Geometric,
+    Random,
+    Explicit,
+}
+
+#[derive(PartialEq, Clone, Serialize, Deserialize)]
+pub struct TrackSizes {
+    pub mode: TrackMode,
+    // geometric only, each track is this times the one before
+    pub ratio: f32,
+    // explicit only, comma separated relative sizes, repeated when short
+    pub list: String,
+    // largest track first instead of last
+    pub reverse: bool,
+}
+
+// recursive split layout, sizes are in millimetres
+#[derive(PartialEq, Clone, Serialize, Deserialize)]
+pub struct BspSettings {
+    // where along the block the cut can fall, 0.5 = always in the middle
+    pub split_min: f32,
+    pub split_max: f32,
+    pub max_depth: u32,
+    pub min_size: f32,
+}
+
+// circle packing layout, sizes are in millimetres
+#[derive(PartialEq, Clone, Serialize, Deserialize)]
+pub struct PackingSettings {
+    pub min_radius: f32,
+    pub max_radius: f32,
+    // space left between neighbouring circles
+    pub padding: f32,
+    // random spots tried before the page counts as full
+    pub attempts: u32,
+}
+
+// poisson disc scatter, sizes are in millimetres
+#[derive(PartialEq, Clone, Serialize, Deserialize)]
+pub struct PoissonSettings {
+    // no two items are closer than this (centre to centre)
+    pub min_distance: f32,
+    pub item_size: f32,
+    // -1..1, positive shrinks items towards the page edge, negative grows them
+    pub centre_falloff: f32,
+    // tries around each point before it is retired, bridson uses 30
+    pub samples: u32,
+}
+
+// where the voronoi / delaunay sites come from
+#[derive(PartialEq, Clone, Copy, Debug, Serialize, Deserialize)]
+pub enum PointSeeding {
+    Random,
+    Poisson,
+    JitteredGrid,
+}
+
+// voronoi / delaunay tessellation, sizes are in millimetres
+#[derive(PartialEq, Clone, Serialize, Deserialize)]
+pub struct TessellationSettings {
+    pub seeding: PointSeeding,
+    // random seeding only
+    pub point_count: u32,
+    // poisson seeding only, smallest distance between sites
+    pub spacing: f32,
+    // jittered grid only (one site per row / col cell), 0 = no jitter
+    pub jitter: f32,
+    // lloyd relaxation passes, evens the cells out
+    pub relax_iterations: u32,
+}
+
+// optional pass that joins same shape cells across rows and columns
+#[derive(PartialEq, Clone, Serialize, Deserialize)]
+pub struct BlockMerge {
+    pub enabled: bool,
+    // chance that a cell tries to grow into a block
+    pub chance: f32,
+    pub max_cols: u32,
+    pub max_rows: u32,
+}
+
+#[derive(PartialEq, Clone, Copy, Debug, Serialize, Deserialize)]
+pub enum RotationMode {
+    None,
+    Random,
+    Fixed,
+    // only triangles are turned, the rest stay upright
+    TowardCentre,
+    AwayFromCentre,
+}
+
+#[derive(PartialEq, Clone, Copy, Debug, Serialize, Deserialize)]
+pub enum StrokeMode {
+    Palette,
+    Ink,
+    None,
+}
+
+// relative chance of each roughr fill style, 0 turns a style off
+#[derive(PartialEq, Clone, Serialize, Deserialize)]
+pub struct FillStyleWeights {
+    pub solid: f32,
+    pub hachure: f32,
+    pub zigzag: f32,
+    pub cross_hatch: f32,
+    pub dots: f32,
+    pub dashed: f32,
+    pub zigzag_line: f32,
+}
+
+impl FillStyleWeights {
+    pub fn entries(&self) -> [(FillStyle, f32); 7] {
+        [
+            (FillStyle::Solid, self.solid),
+            (FillStyle::Hachure, self.hachure),
+            (FillStyle::ZigZag, self.zigzag),
+            (FillStyle::CrossHatch, self.cross_hatch),
+            (FillStyle::Dots, self.dots),
+            (FillStyle::Dashed, self.dashed),
+            (FillStyle::ZigZagLine, self.zigzag_line),
+        ]
+    }
+
+    pub fn entries_mut(&mut self) -> [(&'static str, &mut f32); 7] {
+        [
+            ("Solid", &mut self.solid),
+            ("Hachure", &mut self.hachure),
+            ("ZigZag", &mut self.zigzag),
+            ("Cross Hatch", &mut self.cross_hatch),
+            ("Dots", &mut self.dots),
+            ("Dashed", &mut self.dashed),
+            ("ZigZag Line", &mut self.zigzag_line),
+        ]
+    }
+
+    pub fn pick(&self, rng: &mut ChaCha12Rng) -> Option<FillStyle> {
+        let entries = self.entries();
+        let weights: Vec<f32> = entries.iter().map(|(_, w)| *w).collect();
+
+        carbon_random::pick_weighted(rng, &weights)
+            .map(|i| entries[i].0.clone())
+    }
+}
+
+#[derive(Clone, Serialize, Deserialize)]
 pub struct Settings {
     pub show_grid: bool,
 
-    pub page_padding: i32,
+    // master seed, every random choice is derived from this
+    pub seed: u64,
+    pub seed_text: String,
+
+    // name of the layout strategy, see sketch_layout::strategies
+    pub layout: String,
+    pub page: PageSettings,
+    // padding and gap are in millimetres, like every layout size
+    pub page_padding: f32,
     pub col_total: i32,
     pub row_total: i32,
-    pub gap: i32,
+    pub gap: f32,
+    pub row_sizes: TrackSizes,
+    pub col_sizes: TrackSizes,
+    pub block_merge: BlockMerge,
+    pub bsp: BspSettings,
+    pub packing: PackingSettings,
+    pub poisson: PoissonSettings,
+    pub tessellation: TessellationSettings,
+
+    pub shape_weights: ShapeWeights,
+
+    pub rotation_mode: RotationMode,
+    // random mode only turns in 90 degree steps when set
+    pub rotation_quarter_turns: bool,
+    pub fixed_rotation: f32,
+    pub flip_x: bool,
+    pub flip_y: bool,
+
+    pub stroke_mode: StrokeMode,
+    pub stroke_ink: [f32; 3],
+    pub stroke_width: f32,
+    pub stroke_dash: f32,
+    pub stroke_dash_gap: f32,
+
+    pub fill_style_weights: FillStyleWeights,
+
+    // roughr generator, -1 lets roughr pick a value from the stroke width
+    pub roughness: f32,
+    pub bowing: f32,
+    pub max_randomness_offset: f32,
+    pub curve_tightness: f32,
+    pub curve_fitting: f32,
+    pub hachure_angle: f32,
+    pub hachure_gap: f32,
+    pub fill_weight: f32,
+    pub dash_offset: f32,
+    pub dash_gap: f32,
+    pub zigzag_offset: f32,
+}
+
+// resolved look of a single item, picked once when the layout is generated.
+// The roughr options are not saved, they follow from the rest and the settings.
+#[derive(Clone, Serialize, Deserialize)]
+pub struct ItemStyle {
+    #[serde(with = "carbon_serde::colour")]
+    pub fill_colour: Srgba,
+    #[serde(default, with = "carbon_serde::option_colour")]
+    pub stroke_colour: Option<Srgba>,
+    #[serde(default, with = "carbon_serde::fill_style")]
+    pub fill_style: Option<FillStyle>,
+    #[serde(default)]
+    pub seed: u64,
+    #[serde(skip)]
+    pub options: Options,
 }
 
-#[derive(Clone)]
+impl ItemStyle {
+    pub fn roughr_options(&self, settings: &Settings) -> Options {
+        let mut options = OptionsBuilder::default()
+            .seed(self.seed)
+            .fill(self.fill_colour)
+            .fill_style(self.fill_style.clone().unwrap_or(FillStyle::Hachure))
+            .stroke_width(settings.stroke_width)
+            .curve_tightness(settings.curve_tightness)
+            .curve_fitting(settings.curve_fitting)
+            .bowing(settings.bowing)
+            .max_randomness_offset(settings.max_randomness_offset)
+            .roughness(settings.roughness)
+            .hachure_angle(settings.hachure_angle)
+            .hachure_gap(settings.hachure_gap)
+            .fill_weight(settings.fill_weight)
+            .dash_offset(settings.dash_offset)
+            .dash_gap(settings.dash_gap)
+            .zigzag_offset(settings.zigzag_offset)
+            .build()
+            .unwrap();
+
+        // no stroke at all means roughr should skip the outline
+        options.stroke = self.stroke_colour;
+
+        // every fill style weighted to zero, leave the shape empty
+        if self.fill_style.is_none() {
+            options.fill = None;
+            options.fill_style = None;
+        }
+
+        if settings.stroke_dash > 0.0 {
+            options.stroke_line_dash = Some(vec![
+                settings.stroke_dash as f64,
+                settings.stroke_dash_gap as f64,
+            ]);
+        }
+
+        options
+    }
+}
+
+// applied to the roughr geometry around the centre of the item, flips first
+#[derive(Clone, Copy, PartialEq, Debug, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ItemTransform {
+    // degrees, counter clockwise
+    pub rotation: f32,
+    pub flip_x: bool,
+    pub flip_y: bool,
+}
+
+#[derive(Clone, Serialize, Deserialize)]
 pub struct LayoutItem {
     pub shape: Shapes,
+    // the box shapes are fitted to, the whole cell for rectangular layouts
+    #[serde(with = "carbon_serde::rect")]
     pub dimensions: Rect,
+    // outline of the cell for the hex / triangle tilings, None when the cell
+    // is just `dimensions`. A square fills the whole cell polygon.
+    #[serde(default, with = "carbon_serde::points")]
+    pub cell: Option<Vec<Point2>>,
+    // grid cells or tiles joined into this item, 1 when nothing was merged
+    #[serde(default = "single_cell")]
+    pub cell_count: u32,
+    #[serde(default)]
+    pub transform: ItemTransform,
+    #[serde(default)]
+    pub style: Option<ItemStyle>,
+}
+
+fn single_cell() -> u32 {
+    1
+}
+
+impl LayoutItem {
+    // one unmerged cell, orientation and style are filled in by later passes
+    pub fn new(shape: Shapes, dimensions: Rect) -> LayoutItem {
+        LayoutItem {
+            shape,
+            dimensions,
+            cell: None,
+            cell_count: single_cell(),
+            transform: ItemTransform::default(),
+            style: None,
+        }
+    }
 }
 
 pub struct HigResWorker {
@@ -46,33 +571,165 @@ pub struct HigResWorker {
 
 pub struct Model {
     pub is_setup: bool,
-    pub render_complete: bool,
     pub settings: Settings,
     pub e_gui: Option<Egui>,
     pub high_res_worker: Option<HigResWorker>,
     pub layout: Option<Vec<Vec<LayoutItem>>>,
+    pub scene: Option<Scene>,
     pub raw_palette: Option<[String; 5]>,
+    // json file path on native, pasted json in the browser
+    pub json_input: String,
+    // the layout came from a file, draw it as is instead of generating
+    pub composition_loaded: bool,
+    // presets panel, see sketch_presets
+    pub preset_names: Vec<String>,
+    pub selected_preset: String,
+    pub preset_name_input: String,
+    // last preset / json error, shown in the panel since println goes
+    // nowhere in the browser
+    pub last_error: Option<String>,
+    pub history: History,
+    // the current history entry belongs to the drag still going on
+    pub history_drag: bool,
 }
 
 impl Model {
     pub fn new() -> Model {
+        let seed = carbon_random::random_seed();
+
         Model {
             is_setup: false,
-            render_complete: false,
             settings: Settings {
                 show_grid: true,
 
-                page_padding: 30,
+                seed,
+                seed_text: seed.to_string(),
+
+                layout: "grid".to_string(),
+                page: PageSettings {
+                    size: PageSize::A4,
+                    orientation: Orientation::Portrait,
+                    custom_width: 200.0,
+                    custom_height: 200.0,
+                    dpi: 300.0,
+                },
+                page_padding: 14.0,
                 col_total: 14,
                 row_total: 20,
-                gap: 1,
+                gap: 0.5,
+                row_sizes: TrackSizes {
+                    mode: TrackMode::Uniform,
+                    ratio: 1.2,
+                    list: "1, 2, 3".to_string(),
+                    reverse: false,
+                },
+                col_sizes: TrackSizes {
+                    mode: TrackMode::Uniform,
+                    ratio: 1.2,
+                    list: "1, 2, 3".to_string(),
+                    reverse: false,
+                },
+                block_merge: BlockMerge {
+                    enabled: false,
+                    chance: 0.5,
+                    max_cols: 4,
+                    max_rows: 4,
+                },
+                bsp: BspSettings {
+                    split_min: 0.3,
+                    split_max: 0.7,
+                    max_depth: 6,
+                    min_size: 18.0,
+                },
+                packing: PackingSettings {
+                    min_radius: 3.0,
+                    max_radius: 28.0,
+                    padding: 2.0,
+                    attempts: 3000,
+                },
+                poisson: PoissonSettings {
+                    min_distance: 18.0,
+                    item_size: 14.0,
+                    centre_falloff: 0.0,
+                    samples: 30,
+                },
+                tessellation: TessellationSettings {
+                    seeding: PointSeeding::Poisson,
+                    point_count: 120,
+                    spacing: 20.0,
+                    jitter: 0.6,
+                    relax_iterations: 2,
+                },
+
+                shape_weights: ShapeWeights {
+                    square: 8.0,
+                    circle: 1.0,
+                    triangle: 1.0,
+                    hexagon: 0.0,
+                    diamond: 0.0,
+                    star: 0.0,
+                    semicircle: 0.0,
+                    quarter_circle: 0.0,
+                    arc: 0.0,
+                    cross: 0.0,
+                    rounded_rect: 0.0,
+                    pill: 0.0,
+                    ngon: 0.0,
+                    empty: 0.0,
+
+                    star_points: 5,
+                    ngon_sides: 7,
+                },
+
+                rotation_mode: RotationMode::None,
+                rotation_quarter_turns: true,
+                fixed_rotation: 0.0,
+                flip_x: false,
+                flip_y: false,
+
+                stroke_mode: StrokeMode::Ink,
+                stroke_ink: [0.7, 0.7, 0.7],
+                stroke_width: 1.0,
+                stroke_dash: 0.0,
+                stroke_dash_gap: 4.0,
+
+                fill_style_weights: FillStyleWeights {
+                    solid: 0.0,
+                    hachure: 1.0,
+                    zigzag: 0.0,
+                    cross_hatch: 1.0,
+                    dots: 1.0,
+                    dashed: 1.0,
+                    zigzag_line: 1.0,
+                },
+
+                roughness: 1.0,
+                bowing: 2.0,
+                max_randomness_offset: 2.0,
+                curve_tightness: 0.0,
+                curve_fitting: 0.95,
+                hachure_angle: -41.0,
+                hachure_gap: -1.0,
+                fill_weight: -1.0,
+                dash_offset: -1.0,
+                dash_gap: -1.0,
+                zigzag_offset: -1.0,
             },
 
             // will be setup on first update call
             e_gui: None,
             high_res_worker: None,
             layout: None,
+            scene: None,
             raw_palette: None,
+            json_input: String::new(),
+            composition_loaded: false,
+            preset_names: vec![],
+            selected_preset: String::new(),
+            preset_name_input: String::new(),
+            last_error: None,
+            history: History::new(HISTORY_LIMIT),
+            history_drag: false,
         }
     }
 
@@ -97,10 +754,6 @@ impl Model {
             self.e_gui = Some(egui);
         }
 
-        self.raw_palette = Some(
-            carbon::carbon_colors::get_random_palette()
-        );
-
         // mark the model as ready to go
         self.is_setup = true;
     }
@@ -0,0 +1,83 @@
+use roughr::Srgba;
+use tiny_skia::{Color, FillRule, LineCap, LineJoin, Paint, PathBuilder, Pixmap, Stroke, Transform};
+
+use crate::sketch_scene::{Scene, ScenePath};
+
+// Software raster of the scene for the native batch exports, no window or gpu
+// needed. The scene is stretched to `width` x `height` pixels, normally the
+// page size at the print dpi.
+pub fn scene_to_pixmap(scene: &Scene, width: u32, height: u32) -> Result<Pixmap, String> {
+    let mut pixmap = Pixmap::new(width, height)
+        .ok_or_else(|| format!("can't make a {} x {} image", width, height))?;
+    pixmap.fill(colour(&scene.background));
+
+    // scene space has the origin in the centre and y up
+    let scale = width as f32 / scene.width;
+    let transform = Transform::from_row(
+        scale, 0.0,
+        0.0, -scale,
+        width as f32 / 2.0, height as f32 / 2.0,
+    );
+
+    for path in scene.paths.iter() {
+        draw_path(&mut pixmap, path, transform);
+    }
+
+    Ok(pixmap)
+}
+
+pub fn save_png(scene: &Scene, width: u32, height: u32, file_path: &str) -> Result<(), String> {
+    scene_to_pixmap(scene, width, height)?
+        .save_png(file_path)
+        .map_err(|e| format!("could not save {}: {}", file_path, e))
+}
+
+
+fn draw_path(pixmap: &mut Pixmap, path: &ScenePath, transform: Transform) {
+    for segment in path.segments.iter() {
+        if segment.len() < 2 {
+            continue;
+        }
+
+        let mut pb = PathBuilder::new();
+        pb.move_to(segment[0].x, segment[0].y);
+        for p in segment.iter().skip(1) {
+            pb.line_to(p.x, p.y);
+        }
+
+        if let Some(fill) = path.fill {
+            pb.close();
+            if let Some(shape) = pb.clone().finish() {
+                pixmap.fill_path(&shape, &paint(&fill), FillRule::Winding, transform, None);
+            }
+        }
+
+        if let Some(stroke_colour) = path.stroke {
+            let stroke = Stroke {
+                width: path.stroke_weight,
+                line_cap: LineCap::Round,
+                line_join: LineJoin::Round,
+                ..Stroke::default()
+            };
+            if let Some(line) = pb.finish() {
+                pixmap.stroke_path(&line, &paint(&stroke_colour), &stroke, transform, None);
+            }
+        }
+    }
+}
+
+fn paint(c: &Srgba) -> Paint<'static> {
+    let mut paint = Paint::default();
+    paint.set_color(colour(c));
+    paint.anti_alias = true;
+    paint
+}
+
+fn colour(c: &Srgba) -> Color {
+    Color::from_rgba(
+        c.red.clamp(0.0, 1.0),
+        c.green.clamp(0.0, 1.0),
+        c.blue.clamp(0.0, 1.0),
+        c.alpha.clamp(0.0, 1.0),
+    ).unwrap()
+}
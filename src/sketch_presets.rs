@@ -0,0 +1,202 @@
+use crate::sketch_json;
+use crate::sketch_model::{Model, Settings};
+
+// Named settings presets. Native builds keep one json file per preset in the
+// config directory, the browser keeps them in localStorage.
+//
+// A preset named `default` is loaded on launch instead of the built in
+// defaults. Presets saved by an older version load fine: fields they don't
+// have are taken from the defaults.
+
+pub const STARTUP_PRESET: &str = "default";
+
+// fills the presets panel and swaps in the `default` preset when there is one
+pub fn apply_startup_preset(model: &mut Model) {
+    let store = PresetStore::default_location();
+    model.preset_names = store.names();
+
+    if model.preset_names.iter().any(|n| n == STARTUP_PRESET) {
+        match store.load(STARTUP_PRESET) {
+            Ok(settings) => {
+                model.settings = settings;
+                model.selected_preset = STARTUP_PRESET.to_string();
+            }
+            Err(e) => model.last_error = Some(e),
+        }
+    }
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+pub struct PresetStore {
+    dir: std::path::PathBuf,
+}
+
+#[cfg(target_arch = "wasm32")]
+pub struct PresetStore {
+    prefix: String,
+}
+
+impl PresetStore {
+    pub fn save(&self, name: &str, settings: &Settings) -> Result<(), String> {
+        check_name(name)?;
+        let text = serde_json::to_string_pretty(settings).unwrap();
+        self.write(name, &text)
+    }
+
+    // names from the command line or a stale selection are checked too, so
+    // nothing outside the presets directory can be read
+    pub fn load(&self, name: &str) -> Result<Settings, String> {
+        check_name(name)?;
+        let text = self.read(name)?;
+        settings_from_json(&text)
+            .map_err(|e| format!("preset {} is broken: {}", name, e))
+    }
+
+    pub fn rename(&self, name: &str, new_name: &str) -> Result<(), String> {
+        check_name(name)?;
+        check_name(new_name)?;
+        if name == new_name {
+            return Ok(());
+        }
+        if self.names().iter().any(|n| n == new_name) {
+            return Err(format!("there is already a preset called {}", new_name));
+        }
+
+        let text = self.read(name)?;
+        self.write(new_name, &text)?;
+        self.delete(name)
+    }
+}
+
+// native: <config dir>/nn_001/presets/<name>.json ---------------------------
+#[cfg(not(target_arch = "wasm32"))]
+impl PresetStore {
+    pub fn new(dir: std::path::PathBuf) -> PresetStore {
+        PresetStore { dir }
+    }
+
+    // $XDG_CONFIG_HOME, ~/.config or %APPDATA%, the working directory when
+    // none of them are set
+    pub fn default_location() -> PresetStore {
+        let config = std::env::var_os("XDG_CONFIG_HOME")
+            .map(std::path::PathBuf::from)
+            .or_else(|| std::env::var_os("APPDATA").map(std::path::PathBuf::from))
+            .or_else(|| std::env::var_os("HOME").map(|h| std::path::PathBuf::from(h).join(".config")))
+            .unwrap_or_else(|| std::path::PathBuf::from("."));
+
+        PresetStore::new(config.join("nn_001").join("presets"))
+    }
+
+    pub fn names(&self) -> Vec<String> {
+        let mut names: Vec<String> = match std::fs::read_dir(&self.dir) {
+            Ok(entries) => entries
+                .filter_map(|e| e.ok())
+                .map(|e| e.path())
+                .filter(|p| p.extension() == Some(std::ffi::OsStr::new("json")))
+                .filter_map(|p| p.file_stem().map(|s| s.to_string_lossy().to_string()))
+                .collect(),
+            Err(_) => vec![],
+        };
+        names.sort();
+        names
+    }
+
+    pub fn delete(&self, name: &str) -> Result<(), String> {
+        check_name(name)?;
+        std::fs::remove_file(self.path(name))
+            .map_err(|e| format!("could not delete preset {}: {}", name, e))
+    }
+
+    fn read(&self, name: &str) -> Result<String, String> {
+        std::fs::read_to_string(self.path(name))
+            .map_err(|e| format!("could not read preset {}: {}", name, e))
+    }
+
+    fn write(&self, name: &str, text: &str) -> Result<(), String> {
+        std::fs::create_dir_all(&self.dir)
+            .and_then(|_| std::fs::write(self.path(name), text))
+            .map_err(|e| format!("could not save preset {}: {}", name, e))
+    }
+
+    fn path(&self, name: &str) -> std::path::PathBuf {
+        self.dir.join(format!("{}.json", name))
+    }
+}
+
+// browser: one localStorage entry per preset ---------------------------------
+#[cfg(target_arch = "wasm32")]
+impl PresetStore {
+    pub fn default_location() -> PresetStore {
+        PresetStore { prefix: "nn_001.preset.".to_string() }
+    }
+
+    pub fn names(&self) -> Vec<String> {
+        let storage = match local_storage() {
+            Some(storage) => storage,
+            None => return vec![],
+        };
+
+        let count = storage.length().unwrap_or(0);
+        let mut names: Vec<String> = (0..count)
+            .filter_map(|i| storage.key(i).ok().flatten())
+            .filter_map(|key| key.strip_prefix(&self.prefix).map(|n| n.to_string()))
+            .collect();
+        names.sort();
+        names
+    }
+
+    pub fn delete(&self, name: &str) -> Result<(), String> {
+        check_name(name)?;
+        local_storage()
+            .ok_or_else(|| "no localStorage in this browser".to_string())?
+            .remove_item(&self.key(name))
+            .map_err(|_| format!("could not delete preset {}", name))
+    }
+
+    fn read(&self, name: &str) -> Result<String, String> {
+        local_storage()
+            .ok_or_else(|| "no localStorage in this browser".to_string())?
+            .get_item(&self.key(name))
+            .ok()
+            .flatten()
+            .ok_or_else(|| format!("there is no preset called {}", name))
+    }
+
+    fn write(&self, name: &str, text: &str) -> Result<(), String> {
+        local_storage()
+            .ok_or_else(|| "no localStorage in this browser".to_string())?
+            .set_item(&self.key(name), text)
+            .map_err(|_| format!("could not save preset {} (storage full?)", name))
+    }
+
+    fn key(&self, name: &str) -> String {
+        format!("{}{}", self.prefix, name)
+    }
+}
+
+#[cfg(target_arch = "wasm32")]
+fn local_storage() -> Option<web_sys::Storage> {
+    web_sys::window()?.local_storage().ok().flatten()
+}
+
+
+// names end up as file names, keep them to something every os accepts
+fn check_name(name: &str) -> Result<(), String> {
+    let ok = !name.trim().is_empty()
+        && name == name.trim()
+        && name.chars().all(|c| c.is_alphanumeric() || c == ' ' || c == '-' || c == '_');
+
+    if ok {
+        Ok(())
+    } else {
+        Err(format!("{:?} is not a valid preset name (letters, digits, spaces, - and _)", name))
+    }
+}
+
+// the preset is laid over the defaults, so settings added since it was saved
+// keep their default value
+pub fn settings_from_json(text: &str) -> Result<Settings, String> {
+    let preset: serde_json::Value = serde_json::from_str(text)
+        .map_err(|e| e.to_string())?;
+    sketch_json::settings_from_value(preset)
+}
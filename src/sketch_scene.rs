@@ -0,0 +1,506 @@
+use std::f32::consts::PI;
+
+use nannou::geom::Rect;
+use points_on_curve::points_on_bezier_curves;
+use roughr::core::{Drawable, Op, OpSetType, OpType, Options};
+use roughr::generator::Generator;
+use euclid::default::Point2D;
+use roughr::Srgba;
+
+use crate::sketch_model::{ItemTransform, LayoutItem, Shapes};
+
+// A headless display list of the composition. Everything is plain geometry so
+// the nannou view, the exporters and the tests can all share the same scene
+// without needing a window or a GPU.
+
+// One roughr op set, flattened into polylines. Each segment is drawn as an
+// open polyline when it has a stroke and as a closed polygon when it has a fill.
+#[derive(Clone, Debug, PartialEq)]
+pub struct ScenePath {
+    pub segments: Vec<Vec<Point2D<f32>>>,
+    pub stroke: Option<Srgba>,
+    pub stroke_weight: f32,
+    pub fill: Option<Srgba>,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct Scene {
+    pub width: f32,
+    pub height: f32,
+    pub background: Srgba,
+    pub paths: Vec<ScenePath>,
+}
+
+impl Scene {
+    pub fn new(width: f32, height: f32) -> Scene {
+        Scene {
+            width,
+            height,
+            background: Srgba::new(1.0, 1.0, 1.0, 1.0),
+            paths: vec![],
+        }
+    }
+
+    pub fn add_drawable(&mut self, drawable: &Drawable<f32>) {
+        sketch_lines(self, drawable);
+    }
+}
+
+
+pub fn build_scene(layout: &[Vec<LayoutItem>], width: f32, height: f32) -> Scene {
+    let mut scene = Scene::new(width, height);
+
+    for item in layout.iter().flatten() {
+        if item.shape == Shapes::Empty {
+            continue;
+        }
+        scene.add_drawable(&build_drawable(item));
+    }
+
+    scene
+}
+
+
+pub fn build_drawable(item: &LayoutItem) -> Drawable<f32> {
+    let options = match &item.style {
+        Some(style) => style.options.clone(),
+        None => Options::default(),
+    };
+
+    let g = Generator::default();
+
+    let draw_item: Drawable<f32>;
+
+    match item.shape {
+        // tiled layouts, the square takes up the whole cell. The cells already
+        // fit together, so the item's transform is left off
+        Shapes::Square if item.cell.is_some() => {
+            let cell: Vec<Point2D<f32>> = item.cell.as_ref().unwrap().iter()
+                .map(|p| Point2D::new(p.x, p.y))
+                .collect();
+
+            return g.polygon::<f32>(
+                &cell,
+                &Some(options.clone()),
+            );
+        }
+
+        Shapes::Square => {
+            draw_item = g.rectangle::<f32>(
+                item.dimensions.x() - (item.dimensions.w() / 2.0),
+                item.dimensions.y() - (item.dimensions.h() / 2.0),
+                item.dimensions.w(),
+                item.dimensions.h(),
+                &Some(options.clone()),
+            );
+        }
+
+        Shapes::Circle => {
+            draw_item = g.ellipse::<f32>(
+                item.dimensions.x(),
+                item.dimensions.y(),
+                item.dimensions.w(),
+                item.dimensions.h(),
+                &Some(options.clone()),
+            );
+        }
+
+        Shapes::Triangle => {
+            let top = Point2D::new(
+                item.dimensions.x(),
+                item.dimensions.y() + (item.dimensions.h() / 2.0),
+            );
+
+            let left = Point2D::new(
+                item.dimensions.x() - (item.dimensions.w() / 2.0),
+                item.dimensions.y() - (item.dimensions.h() / 2.0),
+            );
+
+
+            let right = Point2D::new(
+                item.dimensions.x() + (item.dimensions.w() / 2.0),
+                item.dimensions.y() - (item.dimensions.h() / 2.0),
+            );
+
+            draw_item = g.polygon::<f32>(
+                &[top, left, right],
+                &Some(options.clone()),
+            );
+        }
+
+        Shapes::Hexagon => {
+            draw_item = g.polygon::<f32>(
+                &regular_polygon(&item.dimensions, 6, 0.0),
+                &Some(options.clone()),
+            );
+        }
+
+        Shapes::Diamond => {
+            draw_item = g.polygon::<f32>(
+                &regular_polygon(&item.dimensions, 4, PI / 2.0),
+                &Some(options.clone()),
+            );
+        }
+
+        Shapes::Star { points } => {
+            draw_item = g.polygon::<f32>(
+                &star(&item.dimensions, points, 0.45),
+                &Some(options.clone()),
+            );
+        }
+
+        Shapes::NGon { sides } => {
+            draw_item = g.polygon::<f32>(
+                &regular_polygon(&item.dimensions, sides, PI / 2.0),
+                &Some(options.clone()),
+            );
+        }
+
+        // dome sitting on the bottom edge of the cell
+        Shapes::Semicircle => {
+            draw_item = g.arc::<f32>(
+                item.dimensions.x(),
+                item.dimensions.bottom(),
+                item.dimensions.w(),
+                item.dimensions.h() * 2.0,
+                0.0,
+                PI,
+                true,
+                &Some(options.clone()),
+            );
+        }
+
+        // pie slice anchored in the bottom left corner
+        Shapes::QuarterCircle => {
+            draw_item = g.arc::<f32>(
+                item.dimensions.left(),
+                item.dimensions.bottom(),
+                item.dimensions.w() * 2.0,
+                item.dimensions.h() * 2.0,
+                0.0,
+                PI / 2.0,
+                true,
+                &Some(options.clone()),
+            );
+        }
+
+        // open rainbow arc, never filled
+        Shapes::Arc => {
+            draw_item = g.arc::<f32>(
+                item.dimensions.x(),
+                item.dimensions.bottom(),
+                item.dimensions.w(),
+                item.dimensions.h() * 2.0,
+                0.0,
+                PI,
+                false,
+                &Some(options.clone()),
+            );
+        }
+
+        Shapes::Cross => {
+            draw_item = g.polygon::<f32>(
+                &cross(&item.dimensions, 1.0 / 3.0),
+                &Some(options.clone()),
+            );
+        }
+
+        Shapes::RoundedRect => {
+            let radius = item.dimensions.w().min(item.dimensions.h()) * 0.2;
+            draw_item = g.path::<f32>(
+                rounded_rect_path(&item.dimensions, radius),
+                &Some(options.clone()),
+            );
+        }
+
+        Shapes::Pill => {
+            let radius = item.dimensions.w().min(item.dimensions.h()) / 2.0;
+            draw_item = g.path::<f32>(
+                rounded_rect_path(&item.dimensions, radius),
+                &Some(options.clone()),
+            );
+        }
+
+        _ => {
+            draw_item = g.rectangle::<f32>(
+                item.dimensions.x() - (item.dimensions.w() / 2.0),
+                item.dimensions.y() - (item.dimensions.h() / 2.0),
+                item.dimensions.w(),
+                item.dimensions.h(),
+                &Some(options.clone()),
+            );
+        }
+    }
+
+    let mut draw_item = draw_item;
+    if item.transform != ItemTransform::default() {
+        transform_drawable(&mut draw_item, &item.transform, item.dimensions.x(), item.dimensions.y());
+    }
+
+    draw_item
+}
+
+
+// flip then rotate every op point around (cx, cy), fills included
+fn transform_drawable(drawable: &mut Drawable<f32>, transform: &ItemTransform, cx: f32, cy: f32) {
+    let (sin, cos) = transform.rotation.to_radians().sin_cos();
+
+    for set in drawable.sets.iter_mut() {
+        for op in set.ops.iter_mut() {
+            for point in op.data.chunks_mut(2) {
+                if point.len() < 2 {
+                    continue;
+                }
+
+                let mut x = point[0] - cx;
+                let mut y = point[1] - cy;
+
+                if transform.flip_x {
+                    x = -x;
+                }
+                if transform.flip_y {
+                    y = -y;
+                }
+
+                point[0] = cx + x * cos - y * sin;
+                point[1] = cy + x * sin + y * cos;
+            }
+        }
+    }
+}
+
+
+// points around the ellipse that fits the rect, starting at `start_angle`
+fn regular_polygon(rect: &Rect, sides: u32, start_angle: f32) -> Vec<Point2D<f32>> {
+    let sides = sides.max(3);
+
+    (0..sides)
+        .map(|i| {
+            let a = start_angle + (i as f32 / sides as f32) * PI * 2.0;
+            Point2D::new(
+                rect.x() + a.cos() * rect.w() / 2.0,
+                rect.y() + a.sin() * rect.h() / 2.0,
+            )
+        })
+        .collect()
+}
+
+
+// alternating outer and inner points, first point straight up
+fn star(rect: &Rect, points: u32, inner_ratio: f32) -> Vec<Point2D<f32>> {
+    let points = points.max(3);
+    let steps = points * 2;
+
+    (0..steps)
+        .map(|i| {
+            let a = PI / 2.0 + (i as f32 / steps as f32) * PI * 2.0;
+            let r = if i % 2 == 0 { 1.0 } else { inner_ratio };
+            Point2D::new(
+                rect.x() + a.cos() * r * rect.w() / 2.0,
+                rect.y() + a.sin() * r * rect.h() / 2.0,
+            )
+        })
+        .collect()
+}
+
+
+// plus sign, `arm` is the thickness of each bar as a fraction of the cell
+fn cross(rect: &Rect, arm: f32) -> Vec<Point2D<f32>> {
+    let hw = rect.w() * arm / 2.0;
+    let hh = rect.h() * arm / 2.0;
+    let (x, y) = (rect.x(), rect.y());
+    let (l, r, b, t) = (rect.left(), rect.right(), rect.bottom(), rect.top());
+
+    vec![
+        Point2D::new(x - hw, t),
+        Point2D::new(x + hw, t),
+        Point2D::new(x + hw, y + hh),
+        Point2D::new(r, y + hh),
+        Point2D::new(r, y - hh),
+        Point2D::new(x + hw, y - hh),
+        Point2D::new(x + hw, b),
+        Point2D::new(x - hw, b),
+        Point2D::new(x - hw, y - hh),
+        Point2D::new(l, y - hh),
+        Point2D::new(l, y + hh),
+        Point2D::new(x - hw, y + hh),
+    ]
+}
+
+
+// svg path for roughr, corners are quadratic curves of `radius`
+fn rounded_rect_path(rect: &Rect, radius: f32) -> String {
+    let (l, r, b, t) = (rect.left(), rect.right(), rect.bottom(), rect.top());
+    let radius = radius.min(rect.w() / 2.0).min(rect.h() / 2.0);
+
+    format!(
+        "M{} {} L{} {} Q{} {} {} {} L{} {} Q{} {} {} {} L{} {} Q{} {} {} {} L{} {} Q{} {} {} {} Z",
+        l + radius, b,
+        r - radius, b,
+        r, b, r, b + radius,
+        r, t - radius,
+        r, t, r - radius, t,
+        l + radius, t,
+        l, t, l, t - radius,
+        l, b + radius,
+        l, b, l + radius, b,
+    )
+}
+
+
+fn sketch_lines(scene: &mut Scene, lp: &Drawable<f32>) {
+    for set in lp.sets.iter() {
+        // outlines, skipped entirely when there is no stroke colour
+        if set.op_set_type == OpSetType::Path && lp.options.stroke.is_some() {
+            let mut segments = ops_to_segments(&set.ops);
+
+            if let Some(dash) = &lp.options.stroke_line_dash {
+                segments = segments.iter()
+                    .flat_map(|segment| dash_segment(segment, dash))
+                    .collect();
+            }
+
+            scene.paths.push(
+                ScenePath {
+                    segments,
+                    stroke: lp.options.stroke,
+                    stroke_weight: lp.options.stroke_width.unwrap_or(1.0),
+                    fill: None,
+                }
+            );
+        }
+
+        // hachure style fills are lines in the fill colour, a negative fill
+        // weight means half the stroke width like roughr's own paths
+        if set.op_set_type == OpSetType::FillSketch {
+            let sb_fill = lp.options.fill.unwrap();
+            let fill_weight = match lp.options.fill_weight {
+                Some(weight) if weight >= 0.0 => weight,
+                _ => lp.options.stroke_width.unwrap_or(1.0) / 2.0,
+            };
+
+            scene.paths.push(
+                ScenePath {
+                    segments: ops_to_segments(&set.ops),
+                    stroke: Some(sb_fill),
+                    stroke_weight: fill_weight,
+                    fill: None,
+                }
+            );
+        }
+
+        // solid fills, each segment becomes a filled polygon
+        if set.op_set_type == OpSetType::FillPath {
+            let sb_fill = lp.options.fill.unwrap();
+
+            scene.paths.push(
+                ScenePath {
+                    segments: ops_to_segments(&set.ops),
+                    stroke: None,
+                    stroke_weight: 0.0,
+                    fill: Some(sb_fill),
+                }
+            );
+        }
+    }
+}
+
+
+// split the ops into one point list per Move, flattening any bezier curves
+fn ops_to_segments(ops: &[Op<f32>]) -> Vec<Vec<Point2D<f32>>> {
+    let mut segments = vec![];
+    let mut points: Vec<Point2D<f32>> = Vec::new();
+
+    for item in ops {
+        match item.op {
+            OpType::Move => {
+
+                // if we are about to move - keep the points so far
+                if !points.is_empty() {
+                    segments.push(points.clone());
+                    points.clear();
+                }
+
+                points.push(Point2D::new(item.data[0], item.data[1]));
+            }
+            OpType::LineTo => {
+                points.push(Point2D::new(item.data[0], item.data[1]));
+            }
+            OpType::BCurveTo => {
+                let mut curve_points = Vec::new();
+                let last_point = points.last().clone().unwrap();
+                curve_points.push(Point2D::new(
+                    last_point.x,
+                    last_point.y,
+                ));
+
+                curve_points.push(Point2D::new(item.data[0], item.data[1]));
+                curve_points.push(Point2D::new(item.data[2], item.data[3]));
+                curve_points.push(Point2D::new(item.data[4], item.data[5]));
+
+                let result_015 = points_on_bezier_curves(&curve_points, 0.2, Some(0.01));
+
+                for p in result_015 {
+                    points.push(p)
+                }
+            }
+        }
+    }
+
+    if !points.is_empty() {
+        segments.push(points);
+    }
+
+    segments
+}
+
+
+// cut a polyline into the "on" pieces of a dash pattern (dash, gap, dash, ...)
+fn dash_segment(points: &[Point2D<f32>], dash: &[f64]) -> Vec<Vec<Point2D<f32>>> {
+    let pattern: Vec<f32> = dash.iter()
+        .map(|d| *d as f32)
+        .collect();
+
+    if points.len() < 2 || pattern.is_empty() || pattern.iter().any(|d| *d <= 0.0) {
+        return vec![points.to_vec()];
+    }
+
+    let mut dashes = vec![];
+    let mut current = vec![points[0]];
+    let mut index = 0;
+    let mut remaining = pattern[0];
+    let mut on = true;
+
+    for pair in points.windows(2) {
+        let mut a = pair[0];
+        let b = pair[1];
+
+        while a.distance_to(b) > remaining {
+            let p = a.lerp(b, remaining / a.distance_to(b));
+
+            if on {
+                current.push(p);
+                dashes.push(current);
+                current = vec![];
+            } else {
+                current = vec![p];
+            }
+
+            on = !on;
+            index = (index + 1) % pattern.len();
+            remaining = pattern[index];
+            a = p;
+        }
+
+        remaining -= a.distance_to(b);
+        if on {
+            current.push(b);
+        }
+    }
+
+    if on && current.len() > 1 {
+        dashes.push(current);
+    }
+
+    dashes
+}
@@ -0,0 +1,157 @@
+use std::fmt::Write;
+
+use euclid::default::Point2D;
+use roughr::Srgba;
+
+use crate::carbon::carbon_units;
+use crate::sketch_scene::{Scene, ScenePath};
+
+// Every scene path is one roughr op set, so each one becomes a single svg
+// <path>. The scene lives in nannou's space (origin in the centre, y up) so the
+// points are shifted to the top left corner and the y axis is flipped. The
+// size is given in millimetres so the file prints at the page size.
+pub fn scene_to_svg(scene: &Scene) -> String {
+    let mut svg = String::new();
+
+    writeln!(
+        svg,
+        r#"<svg xmlns="http://www.w3.org/2000/svg" width="{mm_w}mm" height="{mm_h}mm" viewBox="0 0 {w} {h}">"#,
+        mm_w = round_mm(carbon_units::px_to_mm(scene.width, carbon_units::CSS_DPI)),
+        mm_h = round_mm(carbon_units::px_to_mm(scene.height, carbon_units::CSS_DPI)),
+        w = scene.width,
+        h = scene.height,
+    ).unwrap();
+
+    writeln!(
+        svg,
+        r#"  <rect width="100%" height="100%" fill="{}" />"#,
+        svg_colour(&scene.background),
+    ).unwrap();
+
+    for path in scene.paths.iter() {
+        let d = path_data(scene, path);
+        if d.is_empty() {
+            continue;
+        }
+
+        let fill = match path.fill {
+            Some(fill) => format!(
+                r#"fill="{}" fill-opacity="{}""#,
+                svg_colour(&fill),
+                fill.alpha,
+            ),
+            None => r#"fill="none""#.to_string(),
+        };
+
+        let stroke = match path.stroke {
+            Some(stroke) => format!(
+                r#"stroke="{}" stroke-opacity="{}" stroke-width="{}" stroke-linecap="round" stroke-linejoin="round""#,
+                svg_colour(&stroke),
+                stroke.alpha,
+                path.stroke_weight,
+            ),
+            None => r#"stroke="none""#.to_string(),
+        };
+
+        writeln!(svg, r#"  <path d="{}" {} {} />"#, d, fill, stroke).unwrap();
+    }
+
+    svg.push_str("</svg>\n");
+    svg
+}
+
+
+pub fn save_svg(scene: &Scene, file_path: &str) -> std::io::Result<()> {
+    std::fs::write(file_path, scene_to_svg(scene))
+}
+
+
+// native builds write next to the binary, the browser gets a download instead
+#[cfg(not(target_arch = "wasm32"))]
+pub fn export_svg(scene: &Scene, file_name: &str) {
+    match save_svg(scene, file_name) {
+        Ok(_) => println!("saved {}", file_name),
+        Err(e) => println!("could not save {}: {}", file_name, e),
+    }
+}
+
+#[cfg(target_arch = "wasm32")]
+pub fn export_svg(scene: &Scene, file_name: &str) {
+    let data_url = format!(
+        "data:image/svg+xml;charset=utf-8,{}",
+        percent_encode(&scene_to_svg(scene)),
+    );
+    download(&data_url, file_name);
+}
+
+// clicks a throwaway link so the browser saves the data url as a file
+#[cfg(target_arch = "wasm32")]
+pub(crate) fn download(data_url: &str, file_name: &str) {
+    use wasm_bindgen::JsCast;
+
+    let document = web_sys::window().unwrap().document().unwrap();
+    let link = document
+        .create_element("a")
+        .unwrap()
+        .dyn_into::<web_sys::HtmlAnchorElement>()
+        .unwrap();
+
+    link.set_href(data_url);
+    link.set_download(file_name);
+    link.click();
+}
+
+#[cfg(target_arch = "wasm32")]
+pub(crate) fn percent_encode(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    for b in text.bytes() {
+        match b {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'_' | b'.' | b'~' => out.push(b as char),
+            _ => write!(out, "%{:02X}", b).unwrap(),
+        }
+    }
+    out
+}
+
+
+fn path_data(scene: &Scene, path: &ScenePath) -> String {
+    let mut d = String::new();
+
+    for segment in path.segments.iter() {
+        for (i, p) in segment.iter().enumerate() {
+            let (x, y) = to_svg_space(scene, p);
+            let cmd = if i == 0 { 'M' } else { 'L' };
+            write!(d, "{}{:.2} {:.2} ", cmd, x, y).unwrap();
+        }
+
+        if path.fill.is_some() && !segment.is_empty() {
+            d.push_str("Z ");
+        }
+    }
+
+    d.trim_end().to_string()
+}
+
+
+fn to_svg_space(scene: &Scene, p: &Point2D<f32>) -> (f32, f32) {
+    (
+        p.x + scene.width / 2.0,
+        scene.height / 2.0 - p.y,
+    )
+}
+
+
+fn svg_colour(c: &Srgba) -> String {
+    format!(
+        "rgb({},{},{})",
+        (c.red.clamp(0.0, 1.0) * 255.0).round() as u8,
+        (c.green.clamp(0.0, 1.0) * 255.0).round() as u8,
+        (c.blue.clamp(0.0, 1.0) * 255.0).round() as u8,
+    )
+}
+
+
+// a4 comes out as 210.00000001 otherwise
+fn round_mm(mm: f32) -> f32 {
+    (mm * 100.0).round() / 100.0
+}
@@ -0,0 +1,132 @@
+use std::cmp::Ordering;
+use std::collections::BTreeMap;
+use std::fmt::Write;
+
+use serde::Serialize;
+
+use crate::carbon::carbon_colors;
+use crate::sketch_model::{LayoutItem, Settings, Shapes};
+
+// Traits of a generated piece and how often each one comes up over a batch of
+// seeds, so an edition can be checked for rare and common features.
+
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub struct Traits {
+    pub seed: u64,
+    pub seed_text: String,
+    pub layout: String,
+    // None when the palette is not one of the built in ones
+    pub palette_index: Option<usize>,
+    // fill style used by the most items, "none" for unfilled items
+    pub dominant_fill_style: String,
+    pub item_count: usize,
+    pub shape_counts: BTreeMap<String, usize>,
+    // items made of more than one grid cell or tile
+    pub merged_blocks: usize,
+}
+
+pub fn collect_traits(settings: &Settings, palette: &[String; 5], layout: &[Vec<LayoutItem>]) -> Traits {
+    let items: Vec<&LayoutItem> = layout.iter()
+        .flatten()
+        .filter(|item| item.shape != Shapes::Empty)
+        .collect();
+
+    let mut shape_counts = BTreeMap::new();
+    let mut fill_counts: BTreeMap<String, usize> = BTreeMap::new();
+    for item in items.iter() {
+        *shape_counts.entry(item.shape.name().to_string()).or_insert(0) += 1;
+
+        let fill = match item.style.as_ref().and_then(|s| s.fill_style.as_ref()) {
+            Some(style) => format!("{:?}", style),
+            None => "none".to_string(),
+        };
+        *fill_counts.entry(fill).or_insert(0) += 1;
+    }
+
+    // ties go to the name that sorts first so the result doesn't wobble
+    let dominant_fill_style = fill_counts.iter()
+        .max_by(|a, b| a.1.cmp(b.1).then_with(|| b.0.cmp(a.0)))
+        .map(|(name, _)| name.clone())
+        .unwrap_or_else(|| "none".to_string());
+
+    Traits {
+        seed: settings.seed,
+        seed_text: settings.seed_text.clone(),
+        layout: settings.layout.clone(),
+        palette_index: carbon_colors::palette_index(palette),
+        dominant_fill_style,
+        item_count: items.len(),
+        shape_counts,
+        merged_blocks: items.iter().filter(|item| item.cell_count > 1).count(),
+    }
+}
+
+
+// trait name -> value -> number of seeds with it
+pub type Histograms = BTreeMap<String, BTreeMap<String, usize>>;
+
+pub fn histograms(traits: &[Traits]) -> Histograms {
+    let mut histograms = Histograms::new();
+    let mut count = |name: &str, value: String| {
+        *histograms.entry(name.to_string())
+            .or_default()
+            .entry(value)
+            .or_insert(0) += 1;
+    };
+
+    for t in traits.iter() {
+        count("layout", t.layout.clone());
+        count("palette_index", t.palette_index.map_or("other".to_string(), |i| i.to_string()));
+        count("dominant_fill_style", t.dominant_fill_style.clone());
+        count("merged_blocks", t.merged_blocks.to_string());
+        // how many pieces have each shape at all, the rare ones stand out
+        for shape in t.shape_counts.keys() {
+            count("has_shape", shape.clone());
+        }
+    }
+
+    histograms
+}
+
+pub fn histograms_to_csv(histograms: &Histograms, total: usize) -> String {
+    let mut csv = String::from("trait,value,count,percent\n");
+    for (name, values) in histograms.iter() {
+        for (value, count) in sorted_values(values) {
+            writeln!(csv, "{},{},{},{:.1}", name, value, count, percent(count, total)).unwrap();
+        }
+    }
+    csv
+}
+
+pub fn histograms_to_markdown(histograms: &Histograms, total: usize) -> String {
+    let mut md = format!("# Traits over {} seeds\n", total);
+    for (name, values) in histograms.iter() {
+        writeln!(md, "\n## {}\n", name).unwrap();
+        md.push_str("| value | count | % |\n|---|---:|---:|\n");
+        for (value, count) in sorted_values(values) {
+            writeln!(md, "| {} | {} | {:.1} |", value, count, percent(count, total)).unwrap();
+        }
+    }
+    md
+}
+
+
+// numbers in numeric order (2 before 10), everything else by name
+fn sorted_values(values: &BTreeMap<String, usize>) -> Vec<(&str, usize)> {
+    let mut sorted: Vec<(&str, usize)> = values.iter().map(|(v, c)| (v.as_str(), *c)).collect();
+    sorted.sort_by(|a, b| match (a.0.parse::<f64>(), b.0.parse::<f64>()) {
+        (Ok(x), Ok(y)) => x.partial_cmp(&y).unwrap_or(Ordering::Equal),
+        (Ok(_), Err(_)) => Ordering::Less,
+        (Err(_), Ok(_)) => Ordering::Greater,
+        (Err(_), Err(_)) => a.0.cmp(b.0),
+    });
+    sorted
+}
+
+fn percent(count: usize, total: usize) -> f32 {
+    if total == 0 {
+        0.0
+    } else {
+        count as f32 / total as f32 * 100.0
+    }
+}
@@ -0,0 +1,60 @@
+use nn_001_web_test::sketch_edition::{output_stem, parse_seeds, run_edition, EditionFormat};
+use nn_001_web_test::sketch_model::{Model, PageSize};
+
+#[test]
+fn seeds_parse_from_ranges_and_lists() {
+    assert_eq!(parse_seeds("3..6").unwrap(), vec!["3", "4", "5"]);
+    assert_eq!(parse_seeds("3..=6").unwrap(), vec!["3", "4", "5", "6"]);
+    assert_eq!(parse_seeds(" 7, 42 ,sunrise").unwrap(), vec!["7", "42", "sunrise"]);
+
+    assert!(parse_seeds("6..3").is_err());
+    assert!(parse_seeds("a..3").is_err());
+    assert!(parse_seeds(" , ").is_err());
+}
+
+#[test]
+fn output_names_sort_in_edition_order() {
+    assert_eq!(output_stem("run", 7, 50, "42"), "run_007_42");
+    assert_eq!(output_stem("run", 7, 2000, "42"), "run_0007_42");
+    assert_eq!(output_stem("run", 1, 1, "a b/c"), "run_001_a_b_c");
+}
+
+#[test]
+fn edition_writes_every_piece_with_a_sidecar() {
+    let out_dir = std::env::temp_dir().join(format!("nn_001_edition_{}", std::process::id()));
+    let _ = std::fs::remove_dir_all(&out_dir);
+    let out_dir = out_dir.to_string_lossy().to_string();
+
+    let mut settings = Model::new().settings;
+    settings.page.size = PageSize::A5;
+    settings.page.dpi = 30.0;
+    settings.row_total = 4;
+    settings.col_total = 3;
+
+    let seeds = parse_seeds("1..=2").unwrap();
+    let files = run_edition(&settings, &seeds, EditionFormat::Both, &out_dir, "test").unwrap();
+    assert_eq!(files.len(), 6);
+    for file in files.iter() {
+        assert!(std::path::Path::new(file).exists(), "{} is missing", file);
+    }
+
+    let sidecar: serde_json::Value = serde_json::from_str(
+        &std::fs::read_to_string(format!("{}/test_002_2.json", out_dir)).unwrap()
+    ).unwrap();
+    assert_eq!(sidecar["edition"], 2);
+    assert_eq!(sidecar["edition_size"], 2);
+    assert_eq!(sidecar["seed_text"], "2");
+    assert_eq!(sidecar["palette"].as_array().unwrap().len(), 5);
+    assert_eq!(sidecar["settings"]["row_total"], 4);
+
+    // png size follows the page at its dpi
+    let png = tiny_skia::Pixmap::load_png(format!("{}/test_001_1.png", out_dir)).unwrap();
+    assert_eq!((png.width(), png.height()), settings.page.print_pixels());
+
+    // same seed, same piece
+    let again = run_edition(&settings, &seeds[..1], EditionFormat::Svg, &out_dir, "again").unwrap();
+    assert_eq!(
+        std::fs::read_to_string(&again[0]).unwrap(),
+        std::fs::read_to_string(format!("{}/test_001_1.svg", out_dir)).unwrap(),
+    );
+}
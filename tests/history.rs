@@ -0,0 +1,82 @@
+use nn_001_web_test::sketch_history::{History, Snapshot};
+use nn_001_web_test::sketch_model::Model;
+
+fn snapshot(seed: u64) -> Snapshot {
+    let mut settings = Model::new().settings;
+    settings.seed = seed;
+
+    Snapshot {
+        label: format!("seed {}", seed),
+        settings,
+        palette: Default::default(),
+        layout: vec![],
+    }
+}
+
+#[test]
+fn undo_and_redo_walk_the_history() {
+    let mut history = History::new(10);
+    assert!(!history.can_undo());
+
+    for seed in 1..=3 {
+        history.push(snapshot(seed));
+    }
+    assert_eq!(history.current(), 2);
+    assert!(!history.can_redo());
+
+    assert_eq!(history.undo().unwrap().settings.seed, 2);
+    assert_eq!(history.undo().unwrap().settings.seed, 1);
+    assert!(history.undo().is_none());
+    assert_eq!(history.redo().unwrap().settings.seed, 2);
+
+    // a new generation after undo drops what was undone
+    history.push(snapshot(4));
+    let seeds: Vec<u64> = history.entries().iter().map(|s| s.settings.seed).collect();
+    assert_eq!(seeds, vec![1, 2, 4]);
+    assert!(!history.can_redo());
+
+    assert_eq!(history.jump(0).unwrap().settings.seed, 1);
+    assert!(history.jump(5).is_none());
+    assert_eq!(history.current(), 0);
+}
+
+#[test]
+fn history_is_bounded() {
+    let mut history = History::new(3);
+    for seed in 1..=5 {
+        history.push(snapshot(seed));
+    }
+
+    let seeds: Vec<u64> = history.entries().iter().map(|s| s.settings.seed).collect();
+    assert_eq!(seeds, vec![3, 4, 5]);
+    assert_eq!(history.current(), 2);
+    assert_eq!(history.pushed(), 5);
+}
+
+#[test]
+fn replacing_keeps_the_label() {
+    let mut history = History::new(10);
+    history.push(snapshot(1));
+    history.push(snapshot(2));
+    history.replace_current(snapshot(3));
+
+    assert_eq!(history.entries().len(), 2);
+    assert_eq!(history.entries()[1].label, "seed 2");
+    assert_eq!(history.entries()[1].settings.seed, 3);
+}
+
+#[test]
+fn replacing_after_undo_starts_a_branch() {
+    let mut history = History::new(10);
+    for seed in 1..=3 {
+        history.push(snapshot(seed));
+    }
+    history.undo();
+    history.replace_current(snapshot(4));
+
+    // the entry we went back to is kept and the undone one is gone
+    let seeds: Vec<u64> = history.entries().iter().map(|s| s.settings.seed).collect();
+    assert_eq!(seeds, vec![1, 2, 4]);
+    assert_eq!(history.current(), 2);
+    assert!(!history.can_redo());
+}
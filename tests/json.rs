@@ -0,0 +1,124 @@
+use nannou::geom::Rect;
+use roughr::core::{FillStyle, Options};
+use roughr::Srgba;
+use nn_001_web_test::sketch_json::{composition_from_json, composition_to_json};
+use nn_001_web_test::sketch_model::{ItemStyle, ItemTransform, LayoutItem, Model, Shapes};
+use nn_001_web_test::sketch_scene::build_scene;
+
+fn styled_item(shape: Shapes, x: f32, fill_style: Option<FillStyle>, seed: u64) -> LayoutItem {
+    let settings = Model::new().settings;
+    let mut style = ItemStyle {
+        fill_colour: Srgba::new(0.2, 0.4, 0.6, 1.0),
+        stroke_colour: Some(Srgba::new(0.1, 0.1, 0.1, 1.0)),
+        fill_style,
+        seed,
+        options: Options::default(),
+    };
+    style.options = style.roughr_options(&settings);
+
+    LayoutItem {
+        transform: ItemTransform { rotation: 30.0, flip_x: true, flip_y: false },
+        style: Some(style),
+        ..LayoutItem::new(shape, Rect::from_x_y_w_h(x, 10.0, 60.0, 40.0))
+    }
+}
+
+// saving and loading gives back the exact same drawing
+#[test]
+fn composition_round_trips() {
+    let model = Model::new();
+    let palette = [
+        "#264653".to_string(),
+        "#2a9d8f".to_string(),
+        "#e9c46a".to_string(),
+        "#f4a261".to_string(),
+        "#e76f51".to_string(),
+    ];
+    let mut layout = vec![vec![
+        styled_item(Shapes::Star { points: 6 }, -50.0, Some(FillStyle::CrossHatch), 11),
+        styled_item(Shapes::Circle, 50.0, None, 12),
+    ]];
+    layout[0][1].cell = Some(vec![
+        nannou::geom::pt2(20.0, -10.0),
+        nannou::geom::pt2(80.0, -10.0),
+        nannou::geom::pt2(50.0, 40.0),
+    ]);
+
+    let text = composition_to_json(&model.settings, &palette, &layout);
+    let loaded = composition_from_json(&text).unwrap();
+
+    let settings = loaded.settings.unwrap();
+    assert_eq!(settings.seed, model.settings.seed);
+    assert_eq!(loaded.palette.unwrap(), palette);
+
+    let mut loaded_layout = loaded.layout;
+    for item in loaded_layout.iter_mut().flatten() {
+        let style = item.style.as_mut().unwrap();
+        style.options = style.roughr_options(&settings);
+    }
+
+    assert_eq!(loaded_layout[0][0].shape, Shapes::Star { points: 6 });
+    assert_eq!(loaded_layout[0][1].cell, layout[0][1].cell);
+    assert_eq!(
+        build_scene(&layout, 300.0, 200.0),
+        build_scene(&loaded_layout, 300.0, 200.0),
+    );
+}
+
+#[test]
+fn hand_written_layout_loads() {
+    let text = r#"{
+        "layout": [[
+            { "shape": "Square", "dimensions": { "x": 0, "y": 0, "w": 100, "h": 50 } },
+            {
+                "shape": { "NGon": { "sides": 5 } },
+                "dimensions": { "x": 100, "y": 0, "w": 50, "h": 50 },
+                "transform": { "rotation": 45 },
+                "style": { "fill_colour": [1, 0, 0, 1], "fill_style": "Dots", "seed": 3 }
+            }
+        ]]
+    }"#;
+
+    let composition = composition_from_json(text).unwrap();
+    assert!(composition.settings.is_none());
+    assert!(composition.palette.is_none());
+
+    let items: Vec<_> = composition.layout.iter().flatten().collect();
+    assert_eq!(items.len(), 2);
+    assert!(items[0].style.is_none());
+    assert_eq!(items[0].dimensions.w(), 100.0);
+    assert_eq!(items[1].shape, Shapes::NGon { sides: 5 });
+    assert_eq!(items[1].transform.rotation, 45.0);
+
+    let style = items[1].style.as_ref().unwrap();
+    assert_eq!(style.fill_style, Some(FillStyle::Dots));
+    assert!(style.stroke_colour.is_none());
+}
+
+// settings missing from an older file keep their defaults
+#[test]
+fn old_settings_load_over_the_defaults() {
+    let text = r#"{
+        "settings": { "seed": 5, "seed_text": "5", "row_total": 3 },
+        "layout": []
+    }"#;
+
+    let settings = composition_from_json(text).unwrap().settings.unwrap();
+    let defaults = Model::new().settings;
+    assert_eq!(settings.seed, 5);
+    assert_eq!(settings.row_total, 3);
+    assert_eq!(settings.col_total, defaults.col_total);
+    assert_eq!(settings.layout, defaults.layout);
+
+    assert!(composition_from_json(r#"{ "settings": { "row_total": "lots" }, "layout": [] }"#).is_err());
+}
+
+#[test]
+fn bad_files_are_rejected() {
+    assert!(composition_from_json("{}").is_err());
+    assert!(composition_from_json(r#"{ "version": 99, "layout": [] }"#).is_err());
+    assert!(composition_from_json(
+        r#"{ "layout": [[{ "shape": "Square", "dimensions": { "x": 0, "y": 0, "w": 1, "h": 1 },
+             "style": { "fill_colour": [0, 0, 0, 1], "fill_style": "Glitter" } }]] }"#
+    ).is_err());
+}
@@ -0,0 +1,342 @@
+use nannou::geom::{pt2, Rect};
+use nn_001_web_test::carbon::carbon_polygon;
+use nn_001_web_test::carbon::carbon_random::{self, RandomStream};
+use nn_001_web_test::sketch_layout;
+use nn_001_web_test::sketch_model::{BlockMerge, Model, Orientation, PageSize, PointSeeding, Shapes, TrackMode};
+
+#[test]
+fn strategies_are_found_by_name() {
+    for name in sketch_layout::strategy_names() {
+        let strategy = sketch_layout::find_strategy(name).unwrap();
+        assert_eq!(strategy.name(), name);
+    }
+
+    assert!(sketch_layout::find_strategy("grid").is_some());
+    assert!(sketch_layout::find_strategy("bsp").is_some());
+    assert!(sketch_layout::find_strategy("nope").is_none());
+}
+
+// Every strategy keeps its items on the padded page and is repeatable.
+#[test]
+fn strategies_stay_inside_the_page() {
+    let mut settings = Model::new().settings;
+    settings.seed = 7;
+    let page = settings.page.rect_mm();
+
+    for name in sketch_layout::strategy_names() {
+        settings.layout = name.to_string();
+        let strategy = sketch_layout::find_strategy(name).unwrap();
+        let padded = page.pad(settings.page_padding - 0.01);
+
+        let mut shape_rng = carbon_random::stream_rng(settings.seed, RandomStream::Shape);
+        let mut layout_rng = carbon_random::stream_rng(settings.seed, RandomStream::Layout);
+        let layout = strategy.generate(page, &settings, &mut shape_rng, &mut layout_rng);
+
+        let mut shape_rng = carbon_random::stream_rng(settings.seed, RandomStream::Shape);
+        let mut layout_rng = carbon_random::stream_rng(settings.seed, RandomStream::Layout);
+        let again = strategy.generate(page, &settings, &mut shape_rng, &mut layout_rng);
+
+        let items: Vec<_> = layout.iter().flatten().collect();
+        assert!(!items.is_empty(), "{} made no items", name);
+        for item in items.iter() {
+            let d = item.dimensions;
+            assert!(
+                padded.contains(d.bottom_left()) && padded.contains(d.top_right()),
+                "{} item outside the page",
+                name,
+            );
+            for p in item.cell.iter().flatten() {
+                assert!(padded.contains(*p), "{} cell outside the page", name);
+            }
+        }
+
+        let dims: Vec<_> = items.iter().map(|i| i.dimensions).collect();
+        let again_dims: Vec<_> = again.iter().flatten().map(|i| i.dimensions).collect();
+        assert_eq!(dims, again_dims, "{} is not repeatable", name);
+    }
+}
+
+// bsp leaves are cut on the rows / cols grid and tile the padded page
+#[test]
+fn bsp_leaves_sit_on_the_grid() {
+    let mut settings = Model::new().settings;
+    settings.gap = 0.0;
+    settings.row_total = 12;
+    settings.col_total = 8;
+    settings.bsp.min_size = 20.0;
+    let page = settings.page.rect_mm();
+    let padded = page.pad(settings.page_padding);
+    let (step_x, step_y) = (padded.w() / 8.0, padded.h() / 12.0);
+    let on_grid = |value: f32, origin: f32, step: f32| {
+        let steps = (value - origin) / step;
+        (steps - steps.round()).abs() < 0.001
+    };
+
+    let strategy = sketch_layout::find_strategy("bsp").unwrap();
+
+    for seed in 0..5 {
+        let mut shape_rng = carbon_random::stream_rng(seed, RandomStream::Shape);
+        let mut layout_rng = carbon_random::stream_rng(seed, RandomStream::Layout);
+        let layout = strategy.generate(page, &settings, &mut shape_rng, &mut layout_rng);
+        let leaves: Vec<Rect> = layout.iter().flatten().map(|i| i.dimensions).collect();
+        assert!(leaves.len() > 1);
+
+        for leaf in leaves.iter() {
+            assert!(on_grid(leaf.left(), padded.left(), step_x));
+            assert!(on_grid(leaf.right(), padded.left(), step_x));
+            assert!(on_grid(leaf.bottom(), padded.bottom(), step_y));
+            assert!(on_grid(leaf.top(), padded.bottom(), step_y));
+            assert!(leaf.w() >= settings.bsp.min_size - 0.001);
+            assert!(leaf.h() >= settings.bsp.min_size - 0.001);
+        }
+
+        for (i, a) in leaves.iter().enumerate() {
+            for b in leaves.iter().skip(i + 1) {
+                let overlap_w = a.right().min(b.right()) - a.left().max(b.left());
+                let overlap_h = a.top().min(b.top()) - a.bottom().max(b.bottom());
+                assert!(overlap_w < 0.001 || overlap_h < 0.001, "seed {} leaves overlap", seed);
+            }
+        }
+
+        // no overlap and the same area means the page is covered
+        let area: f32 = leaves.iter().map(|l| l.w() * l.h()).sum();
+        assert!((area - padded.w() * padded.h()).abs() < 1.0);
+    }
+}
+
+// blocks only grow over matching cells and keep the gap on every side
+#[test]
+fn merged_blocks_keep_gaps_and_skip_empty_cells() {
+    let r = Rect::from_corners(pt2(0.0, 0.0), pt2(60.0, 40.0));
+    let gap = 1.0;
+    let block_merge = BlockMerge {
+        enabled: true,
+        chance: 1.0,
+        max_cols: 6,
+        max_rows: 4,
+    };
+
+    // squares cut up by an empty column and a bit of an empty row
+    let cells: Vec<Vec<Shapes>> = (0..4)
+        .map(|row| {
+            (0..6)
+                .map(|col| if col == 2 || (row == 2 && col > 3) { Shapes::Empty } else { Shapes::Square })
+                .collect()
+        })
+        .collect();
+
+    let mut rng = carbon_random::stream_rng(4, RandomStream::Layout);
+    let layout = sketch_layout::merge_blocks(&cells, r, &[10.0; 4], &[10.0; 6], gap, &block_merge, &mut rng);
+    let items: Vec<_> = layout.iter().flatten().collect();
+    assert!(items.iter().any(|i| i.cell_count > 1));
+
+    let mut covered = vec![vec![0; 6]; 4];
+    for item in items.iter() {
+        // take the gap back off, the block has to sit on whole cells
+        let block = item.dimensions.pad(-gap);
+        let (col, row) = ((block.left() / 10.0).round() as usize, (block.bottom() / 10.0).round() as usize);
+        let (w, h) = ((block.w() / 10.0).round() as usize, (block.h() / 10.0).round() as usize);
+        assert!((block.left() - col as f32 * 10.0).abs() < 0.001);
+        assert!((block.bottom() - row as f32 * 10.0).abs() < 0.001);
+        assert!((block.w() - w as f32 * 10.0).abs() < 0.001);
+        assert!((block.h() - h as f32 * 10.0).abs() < 0.001);
+        assert_eq!(item.cell_count as usize, w * h);
+
+        for cell_row in row..row + h {
+            for cell_col in col..col + w {
+                assert!(cells[cell_row][cell_col] == item.shape);
+                covered[cell_row][cell_col] += 1;
+            }
+        }
+    }
+
+    // every cell ends up in exactly one block
+    assert!(covered.iter().flatten().all(|c| *c == 1));
+}
+#[test]
+fn merged_tiles_have_one_outline() {
+    let page = Rect::from_w_h(450.0, 600.0);
+    let mut settings = Model::new().settings;
+    settings.seed = 3;
+    // squares everywhere so every tile can join its neighbours
+    for (_, weight) in settings.shape_weights.entries_mut() {
+        *weight = 0.0;
+    }
+    settings.shape_weights.square = 1.0;
+
+    for name in ["hex", "triangle"] {
+        let strategy = sketch_layout::find_strategy(name).unwrap();
+
+        settings.block_merge.enabled = false;
+        let mut shape_rng = carbon_random::stream_rng(settings.seed, RandomStream::Shape);
+        let mut layout_rng = carbon_random::stream_rng(settings.seed, RandomStream::Layout);
+        let tiles = strategy.generate(page, &settings, &mut shape_rng, &mut layout_rng);
+
+        settings.block_merge.enabled = true;
+        settings.block_merge.chance = 1.0;
+        let mut shape_rng = carbon_random::stream_rng(settings.seed, RandomStream::Shape);
+        let mut layout_rng = carbon_random::stream_rng(settings.seed, RandomStream::Layout);
+        let merged = strategy.generate(page, &settings, &mut shape_rng, &mut layout_rng);
+
+        let tile_count = tiles.iter().flatten().count();
+        let merged_count = merged.iter().flatten().count();
+        assert!(merged_count < tile_count, "{} did not merge", name);
+
+        let sides = if name == "hex" { 6 } else { 3 };
+        assert!(tiles.iter().flatten().all(|i| i.cell.as_ref().unwrap().len() == sides));
+        assert!(merged.iter().flatten().any(|i| i.cell.as_ref().unwrap().len() > sides));
+
+        // every tile is counted once, in the group it ended up in
+        let counted: u32 = merged.iter().flatten().map(|i| i.cell_count).sum();
+        assert_eq!(counted as usize, tile_count);
+    }
+}
+
+#[test]
+fn packed_circles_do_not_overlap() {
+    let page = Rect::from_w_h(450.0, 600.0);
+    let mut settings = Model::new().settings;
+    settings.seed = 11;
+    for (_, weight) in settings.shape_weights.entries_mut() {
+        *weight = 0.0;
+    }
+    settings.shape_weights.circle = 1.0;
+
+    let strategy = sketch_layout::find_strategy("circle_packing").unwrap();
+    let mut shape_rng = carbon_random::stream_rng(settings.seed, RandomStream::Shape);
+    let mut layout_rng = carbon_random::stream_rng(settings.seed, RandomStream::Layout);
+    let layout = strategy.generate(page, &settings, &mut shape_rng, &mut layout_rng);
+
+    let circles: Vec<_> = layout.iter().flatten().map(|i| i.dimensions).collect();
+    assert!(circles.len() > 10);
+
+    for (i, a) in circles.iter().enumerate() {
+        assert!(a.w() / 2.0 >= settings.packing.min_radius - 0.001);
+        for b in circles.iter().skip(i + 1) {
+            let d = a.xy().distance(b.xy());
+            assert!(d + 0.001 >= a.w() / 2.0 + b.w() / 2.0 + settings.packing.padding);
+        }
+    }
+}
+
+#[test]
+fn poisson_points_keep_their_distance() {
+    let page = Rect::from_w_h(450.0, 600.0);
+    let mut settings = Model::new().settings;
+    settings.seed = 5;
+
+    let strategy = sketch_layout::find_strategy("poisson").unwrap();
+    let mut shape_rng = carbon_random::stream_rng(settings.seed, RandomStream::Shape);
+    let mut layout_rng = carbon_random::stream_rng(settings.seed, RandomStream::Layout);
+    let layout = strategy.generate(page, &settings, &mut shape_rng, &mut layout_rng);
+
+    let centres: Vec<_> = layout.iter().flatten().map(|i| i.dimensions.xy()).collect();
+    assert!(centres.len() > 20);
+
+    for (i, a) in centres.iter().enumerate() {
+        for b in centres.iter().skip(i + 1) {
+            assert!(a.distance(*b) >= settings.poisson.min_distance - 0.001);
+        }
+    }
+}
+
+// a gap wider than the thinnest cells shrinks them instead of dropping them
+#[test]
+fn thin_tessellation_cells_are_kept() {
+    let page = Rect::from_w_h(450.0, 600.0);
+    let mut settings = Model::new().settings;
+    settings.gap = 40.0;
+    settings.block_merge.enabled = false;
+    settings.tessellation.seeding = PointSeeding::Random;
+    settings.tessellation.point_count = 60;
+    settings.tessellation.relax_iterations = 0;
+
+    let strategy = sketch_layout::find_strategy("voronoi").unwrap();
+    let mut shape_rng = carbon_random::stream_rng(settings.seed, RandomStream::Shape);
+    let mut layout_rng = carbon_random::stream_rng(settings.seed, RandomStream::Layout);
+    let layout = strategy.generate(page, &settings, &mut shape_rng, &mut layout_rng);
+    let items: Vec<_> = layout.iter().flatten().collect();
+
+    assert_eq!(items.len(), 60);
+    for item in items {
+        // still counter clockwise, so not folded over
+        assert!(carbon_polygon::signed_area(item.cell.as_ref().unwrap()) > 0.0);
+        assert!(item.dimensions.w() > 0.0 && item.dimensions.h() > 0.0);
+    }
+}
+
+#[test]
+fn track_sizes_fill_the_page() {
+    let mut settings = Model::new().settings;
+    let mut rng = carbon_random::stream_rng(1, RandomStream::Layout);
+
+    for mode in [
+        TrackMode::Uniform,
+        TrackMode::Fibonacci,
+        TrackMode::Golden,
+        TrackMode::Geometric,
+        TrackMode::Random,
+        TrackMode::Explicit,
+    ] {
+        settings.row_sizes.mode = mode;
+        let sizes = sketch_layout::track_sizes(540.0, 7, &settings.row_sizes, &mut rng);
+
+        assert_eq!(sizes.len(), 7);
+        assert!((sizes.iter().sum::<f32>() - 540.0).abs() < 0.01, "{:?}", mode);
+        assert!(sizes.iter().all(|s| *s > 0.0), "{:?}", mode);
+    }
+
+    settings.row_sizes.mode = TrackMode::Fibonacci;
+    let sizes = sketch_layout::track_sizes(20.0, 5, &settings.row_sizes, &mut rng);
+    assert_eq!(sizes, vec![1.0, 1.0, 2.0, 3.0, 5.0].iter().map(|s| s * 20.0 / 12.0).collect::<Vec<f32>>());
+
+    assert_eq!(sketch_layout::parse_track_list("1, 2 x -3 4"), vec![1.0, 2.0, 4.0]);
+}
+
+#[test]
+fn merged_blocks_follow_uneven_tracks() {
+    let page = Rect::from_w_h(450.0, 600.0);
+    let mut settings = Model::new().settings;
+    settings.seed = 9;
+    settings.gap = 0.0;
+    settings.row_sizes.mode = TrackMode::Golden;
+    settings.col_sizes.mode = TrackMode::Fibonacci;
+    settings.row_total = 6;
+    settings.col_total = 5;
+    settings.block_merge.enabled = true;
+    settings.block_merge.chance = 1.0;
+
+    let strategy = sketch_layout::find_strategy("grid").unwrap();
+    let mut shape_rng = carbon_random::stream_rng(settings.seed, RandomStream::Shape);
+    let mut layout_rng = carbon_random::stream_rng(settings.seed, RandomStream::Layout);
+    let layout = strategy.generate(page, &settings, &mut shape_rng, &mut layout_rng);
+
+    // with no gap the blocks tile the padded page exactly
+    let padded = page.pad(settings.page_padding);
+    let area: f32 = layout.iter().flatten().map(|i| i.dimensions.w() * i.dimensions.h()).sum();
+    assert!((area - padded.w() * padded.h()).abs() < 1.0);
+
+    let counted: u32 = layout.iter().flatten().map(|i| i.cell_count).sum();
+    assert_eq!(counted, 6 * 5);
+}
+
+#[test]
+fn pages_are_sized_in_millimetres() {
+    let mut page = Model::new().settings.page;
+    page.size = PageSize::A4;
+    page.orientation = Orientation::Portrait;
+    page.dpi = 300.0;
+
+    assert_eq!(page.size_mm(), (210.0, 297.0));
+    assert_eq!(page.print_pixels(), (2480, 3508));
+    assert!((page.rect_px().w() - 793.7).abs() < 0.1);
+
+    page.orientation = Orientation::Landscape;
+    assert_eq!(page.size_mm(), (297.0, 210.0));
+
+    // custom pages keep the size they were given
+    page.size = PageSize::Custom;
+    page.custom_width = 120.0;
+    page.custom_height = 80.0;
+    assert_eq!(page.size_mm(), (120.0, 80.0));
+}
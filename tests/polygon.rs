@@ -0,0 +1,32 @@
+use nannou::prelude::*;
+use nn_001_web_test::carbon::carbon_polygon;
+
+#[test]
+fn two_triangles_merge_into_a_rhombus() {
+    let up = vec![pt2(0.0, 0.0), pt2(2.0, 0.0), pt2(1.0, 1.0)];
+    let down = vec![pt2(1.0, 1.0), pt2(2.0, 0.0), pt2(3.0, 1.0)];
+
+    let outline = carbon_polygon::merge_outline(&[up, down]).unwrap();
+
+    assert_eq!(outline.len(), 4);
+    assert!((carbon_polygon::signed_area(&outline) - 2.0).abs() < 0.001);
+}
+
+#[test]
+fn corner_touching_tiles_do_not_merge() {
+    let a = vec![pt2(0.0, 0.0), pt2(1.0, 0.0), pt2(1.0, 1.0), pt2(0.0, 1.0)];
+    let b = vec![pt2(1.0, 1.0), pt2(2.0, 1.0), pt2(2.0, 2.0), pt2(1.0, 2.0)];
+
+    assert!(carbon_polygon::merge_outline(&[a, b]).is_none());
+}
+
+#[test]
+fn inset_moves_every_edge_in() {
+    let square = vec![pt2(0.0, 0.0), pt2(10.0, 0.0), pt2(10.0, 10.0), pt2(0.0, 10.0)];
+    let inset = carbon_polygon::inset(&square, 1.0);
+
+    let rect = carbon_polygon::bounding_rect(&inset);
+    assert!((rect.w() - 8.0).abs() < 0.001);
+    assert!((rect.h() - 8.0).abs() < 0.001);
+    assert!((rect.x() - 5.0).abs() < 0.001);
+}
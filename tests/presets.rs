@@ -0,0 +1,74 @@
+use nn_001_web_test::sketch_model::{Model, PageSize};
+use nn_001_web_test::sketch_presets::{settings_from_json, PresetStore};
+
+fn temp_store(name: &str) -> PresetStore {
+    let dir = std::env::temp_dir().join(format!("nn_001_presets_{}_{}", name, std::process::id()));
+    let _ = std::fs::remove_dir_all(&dir);
+    PresetStore::new(dir)
+}
+
+#[test]
+fn presets_save_rename_and_delete() {
+    let store = temp_store("crud");
+    assert!(store.names().is_empty());
+
+    let mut settings = Model::new().settings;
+    settings.layout = "voronoi".to_string();
+    settings.page.size = PageSize::A3;
+    settings.gap = 2.5;
+
+    store.save("big voronoi", &settings).unwrap();
+    store.save("plain", &Model::new().settings).unwrap();
+    assert_eq!(store.names(), vec!["big voronoi", "plain"]);
+
+    let loaded = store.load("big voronoi").unwrap();
+    assert_eq!(loaded.seed, settings.seed);
+    assert_eq!(loaded.layout, "voronoi");
+    assert!(loaded.page == settings.page);
+    assert_eq!(loaded.gap, 2.5);
+
+    // can't rename on top of another preset
+    assert!(store.rename("big voronoi", "plain").is_err());
+    store.rename("big voronoi", "a3_voronoi").unwrap();
+    assert_eq!(store.names(), vec!["a3_voronoi", "plain"]);
+    assert_eq!(store.load("a3_voronoi").unwrap().layout, "voronoi");
+
+    store.delete("plain").unwrap();
+    assert_eq!(store.names(), vec!["a3_voronoi"]);
+    assert!(store.load("plain").is_err());
+}
+
+#[test]
+fn preset_names_must_be_file_safe() {
+    let store = temp_store("names");
+    let settings = Model::new().settings;
+
+    assert!(store.save("", &settings).is_err());
+    assert!(store.save(" padded ", &settings).is_err());
+    assert!(store.save("../escape", &settings).is_err());
+    assert!(store.save("a/b", &settings).is_err());
+    assert!(store.names().is_empty());
+
+    // `--preset ../x` must not reach outside the presets directory either
+    store.save("inside", &settings).unwrap();
+    let outside = std::env::temp_dir().join("nn_001_outside.json");
+    std::fs::write(&outside, "{}").unwrap();
+    assert!(store.load("../nn_001_outside").is_err());
+    assert!(store.rename("../nn_001_outside", "moved").is_err());
+    assert!(store.delete("../nn_001_outside").is_err());
+    assert!(outside.exists());
+}
+
+// presets from before a setting existed still load, with its default
+#[test]
+fn old_presets_fill_in_missing_settings() {
+    let settings = settings_from_json(r#"{ "layout": "hex", "page": { "size": "A5" }, "seed": 42 }"#).unwrap();
+
+    assert_eq!(settings.layout, "hex");
+    assert_eq!(settings.seed, 42);
+    assert!(settings.page.size == PageSize::A5);
+    assert_eq!(settings.page.dpi, Model::new().settings.page.dpi);
+
+    assert!(settings_from_json(r#"{ "layout": 3 }"#).is_err());
+    assert!(settings_from_json("not json").is_err());
+}
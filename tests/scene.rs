@@ -0,0 +1,230 @@
+use nannou::geom::Rect;
+use roughr::core::{FillStyle, OptionsBuilder};
+use roughr::Srgba;
+use nn_001_web_test::carbon::carbon_random::{self, RandomStream};
+use nn_001_web_test::sketch_layout;
+use nn_001_web_test::sketch_model::{ItemStyle, ItemTransform, LayoutItem, Model, Shapes};
+use nn_001_web_test::sketch_scene::build_scene;
+
+fn single_item_layout(shape: Shapes) -> Vec<Vec<LayoutItem>> {
+    vec![vec![
+        LayoutItem::new(shape, Rect::from_x_y_w_h(0.0, 0.0, 100.0, 50.0))
+    ]]
+}
+
+// The scene can be built and inspected without a window.
+#[test]
+fn unstyled_square_is_outline_only() {
+    let scene = build_scene(&single_item_layout(Shapes::Square), 200.0, 200.0);
+
+    assert!(!scene.paths.is_empty());
+    for path in scene.paths.iter() {
+        assert!(path.stroke.is_some());
+        assert!(path.fill.is_none());
+        assert!(path.segments.iter().all(|s| !s.is_empty()));
+    }
+}
+
+#[test]
+fn same_layout_gives_same_scene() {
+    let a = build_scene(&single_item_layout(Shapes::Circle), 200.0, 200.0);
+    let b = build_scene(&single_item_layout(Shapes::Circle), 200.0, 200.0);
+
+    assert_eq!(a, b);
+}
+
+#[test]
+fn solid_fill_becomes_filled_polygon() {
+    let fill = Srgba::new(0.2, 0.4, 0.6, 1.0);
+    let options = OptionsBuilder::default()
+        .fill(fill)
+        .fill_style(FillStyle::Solid)
+        .build()
+        .unwrap();
+
+    let mut layout = single_item_layout(Shapes::Circle);
+    layout[0][0].style = Some(
+        ItemStyle {
+            fill_colour: fill,
+            stroke_colour: Some(Srgba::new(0.0, 0.0, 0.0, 1.0)),
+            fill_style: Some(FillStyle::Solid),
+            seed: 1,
+            options,
+        }
+    );
+
+    let scene = build_scene(&layout, 200.0, 200.0);
+    let filled: Vec<_> = scene.paths.iter().filter(|p| p.fill.is_some()).collect();
+
+    assert_eq!(filled.len(), 1);
+    assert_eq!(filled[0].fill, Some(fill));
+    assert!(filled[0].stroke.is_none());
+    // the ellipse outline is made of bezier curves, they should be flattened
+    assert!(filled[0].segments[0].len() > 4);
+}
+
+#[test]
+fn no_stroke_colour_skips_outlines() {
+    let fill = Srgba::new(0.2, 0.4, 0.6, 1.0);
+    let mut options = OptionsBuilder::default()
+        .fill(fill)
+        .fill_style(FillStyle::Hachure)
+        .build()
+        .unwrap();
+    options.stroke = None;
+
+    let mut layout = single_item_layout(Shapes::Square);
+    layout[0][0].style = Some(
+        ItemStyle {
+            fill_colour: fill,
+            stroke_colour: None,
+            fill_style: Some(FillStyle::Hachure),
+            seed: 1,
+            options,
+        }
+    );
+
+    let scene = build_scene(&layout, 200.0, 200.0);
+
+    assert!(!scene.paths.is_empty());
+    // only the hachure lines are left, drawn in the fill colour
+    assert!(scene.paths.iter().all(|p| p.stroke == Some(fill)));
+}
+
+// weights of the hachure lines, -1 falls back to half the stroke width
+fn fill_line_weights(fill_weight: f32, fill_style: FillStyle) -> Vec<f32> {
+    let fill = Srgba::new(0.2, 0.4, 0.6, 1.0);
+    let options = OptionsBuilder::default()
+        .fill(fill)
+        .fill_style(fill_style.clone())
+        .stroke_width(3.0)
+        .fill_weight(fill_weight)
+        .build()
+        .unwrap();
+
+    let mut layout = single_item_layout(Shapes::Square);
+    layout[0][0].style = Some(
+        ItemStyle {
+            fill_colour: fill,
+            stroke_colour: Some(Srgba::new(0.0, 0.0, 0.0, 1.0)),
+            fill_style: Some(fill_style),
+            seed: 1,
+            options,
+        }
+    );
+
+    build_scene(&layout, 200.0, 200.0).paths.iter()
+        .filter(|p| p.stroke == Some(fill))
+        .map(|p| p.stroke_weight)
+        .collect()
+}
+
+#[test]
+fn fill_weight_sets_fill_line_weight() {
+    for fill_style in [FillStyle::Hachure, FillStyle::ZigZag, FillStyle::CrossHatch, FillStyle::Dashed] {
+        let weights = fill_line_weights(4.0, fill_style.clone());
+        assert!(!weights.is_empty());
+        assert!(weights.iter().all(|w| *w == 4.0), "{:?}", fill_style);
+
+        assert!(fill_line_weights(-1.0, fill_style).iter().all(|w| *w == 1.5));
+    }
+}
+
+#[test]
+fn every_shape_draws_inside_its_cell() {
+    let shapes = vec![
+        Shapes::Square,
+        Shapes::Circle,
+        Shapes::Triangle,
+        Shapes::Hexagon,
+        Shapes::Diamond,
+        Shapes::Star { points: 5 },
+        Shapes::Semicircle,
+        Shapes::QuarterCircle,
+        Shapes::Arc,
+        Shapes::Cross,
+        Shapes::RoundedRect,
+        Shapes::Pill,
+        Shapes::NGon { sides: 7 },
+    ];
+
+    for shape in shapes {
+        let scene = build_scene(&single_item_layout(shape), 200.0, 200.0);
+        assert!(!scene.paths.is_empty());
+
+        // roughness lets strokes wander a few px past the cell (100 x 50)
+        for p in scene.paths.iter().flat_map(|p| p.segments.iter().flatten()) {
+            assert!(p.x.abs() < 60.0 && p.y.abs() < 35.0);
+        }
+    }
+}
+
+#[test]
+fn empty_cells_draw_nothing() {
+    let scene = build_scene(&single_item_layout(Shapes::Empty), 200.0, 200.0);
+    assert!(scene.paths.is_empty());
+}
+
+#[test]
+fn flipped_triangle_points_down() {
+    let mut layout = single_item_layout(Shapes::Triangle);
+    layout[0][0].transform = ItemTransform {
+        rotation: 0.0,
+        flip_x: false,
+        flip_y: true,
+    };
+
+    let scene = build_scene(&layout, 200.0, 200.0);
+
+    // the base is now along the top of the 50px tall cell, so the top band
+    // spans the whole width instead of just the apex
+    let top_width = scene.paths.iter()
+        .flat_map(|p| p.segments.iter().flatten())
+        .filter(|p| p.y > 20.0)
+        .map(|p| p.x.abs())
+        .fold(0.0, f32::max);
+
+    assert!(top_width > 40.0);
+}
+
+// tiles already fit together, turning their cells would leave gaps and overlaps
+#[test]
+fn turned_tiles_keep_their_cells() {
+    let page = Rect::from_w_h(450.0, 600.0);
+    let mut settings = Model::new().settings;
+    for (_, weight) in settings.shape_weights.entries_mut() {
+        *weight = 0.0;
+    }
+    settings.shape_weights.square = 1.0;
+
+    let strategy = sketch_layout::find_strategy("triangle").unwrap();
+    let mut shape_rng = carbon_random::stream_rng(settings.seed, RandomStream::Shape);
+    let mut layout_rng = carbon_random::stream_rng(settings.seed, RandomStream::Layout);
+    let mut layout = strategy.generate(page, &settings, &mut shape_rng, &mut layout_rng);
+
+    // a fixed roughr seed so both scenes wobble the same way
+    for item in layout.iter_mut().flatten() {
+        item.style = Some(
+            ItemStyle {
+                fill_colour: Srgba::new(0.2, 0.4, 0.6, 1.0),
+                stroke_colour: Some(Srgba::new(0.0, 0.0, 0.0, 1.0)),
+                fill_style: Some(FillStyle::Hachure),
+                seed: 1,
+                options: OptionsBuilder::default().seed(1).build().unwrap(),
+            }
+        );
+    }
+    let plain = build_scene(&layout, page.w(), page.h());
+
+    for item in layout.iter_mut().flatten() {
+        item.transform = ItemTransform {
+            rotation: 90.0,
+            flip_x: false,
+            flip_y: true,
+        };
+    }
+    let turned = build_scene(&layout, page.w(), page.h());
+
+    assert!(!plain.paths.is_empty());
+    assert_eq!(plain, turned);
+}
@@ -0,0 +1,51 @@
+use roughr::Point2D;
+use roughr::Srgba;
+use nn_001_web_test::sketch_scene::{Scene, ScenePath};
+use nn_001_web_test::sketch_svg::scene_to_svg;
+
+// nannou is centred with y up, svg starts in the top left with y down
+#[test]
+fn points_are_flipped_into_svg_space() {
+    let mut scene = Scene::new(200.0, 100.0);
+    scene.paths.push(
+        ScenePath {
+            segments: vec![vec![
+                Point2D::new(-100.0, 50.0),
+                Point2D::new(100.0, -50.0),
+            ]],
+            stroke: Some(Srgba::new(1.0, 0.0, 0.0, 1.0)),
+            stroke_weight: 2.0,
+            fill: None,
+        }
+    );
+
+    let svg = scene_to_svg(&scene);
+
+    assert!(svg.contains(r#"d="M0.00 0.00 L200.00 100.00""#));
+    assert!(svg.contains(r#"stroke="rgb(255,0,0)""#));
+    assert!(svg.contains(r#"stroke-width="2""#));
+    assert!(svg.contains(r#"fill="none""#));
+}
+
+#[test]
+fn filled_paths_are_closed() {
+    let mut scene = Scene::new(10.0, 10.0);
+    scene.paths.push(
+        ScenePath {
+            segments: vec![vec![
+                Point2D::new(0.0, 0.0),
+                Point2D::new(1.0, 0.0),
+                Point2D::new(1.0, 1.0),
+            ]],
+            stroke: None,
+            stroke_weight: 1.0,
+            fill: Some(Srgba::new(0.0, 0.0, 1.0, 1.0)),
+        }
+    );
+
+    let svg = scene_to_svg(&scene);
+
+    assert!(svg.contains(r#"d="M5.00 5.00 L6.00 5.00 L6.00 4.00 Z""#));
+    assert!(svg.contains(r#"fill="rgb(0,0,255)""#));
+    assert!(svg.contains(r#"stroke="none""#));
+}
@@ -0,0 +1,44 @@
+use nannou::prelude::*;
+use nn_001_web_test::carbon::{carbon_polygon, carbon_tessellation};
+
+fn sites() -> Vec<Point2> {
+    vec![
+        pt2(-30.0, -20.0),
+        pt2(25.0, -35.0),
+        pt2(10.0, 30.0),
+        pt2(-40.0, 40.0),
+        pt2(40.0, 10.0),
+        pt2(0.0, 0.0),
+    ]
+}
+
+#[test]
+fn voronoi_cells_cover_the_bounds() {
+    let bounds = Rect::from_w_h(100.0, 100.0);
+    let cells = carbon_tessellation::voronoi_cells(&sites(), bounds);
+
+    assert_eq!(cells.len(), sites().len());
+    let area: f32 = cells.iter().map(|c| carbon_polygon::signed_area(c)).sum();
+    assert!((area - 100.0 * 100.0).abs() < 0.1);
+}
+
+#[test]
+fn delaunay_splits_a_square_in_two() {
+    let square = [pt2(0.0, 0.0), pt2(10.0, 0.0), pt2(10.0, 11.0), pt2(0.0, 10.0)];
+    let triangles = carbon_tessellation::delaunay_triangles(&square);
+
+    assert_eq!(triangles.len(), 2);
+    for t in triangles.iter() {
+        let area = carbon_polygon::signed_area(&[square[t[0]], square[t[1]], square[t[2]]]);
+        assert!(area > 0.0);
+    }
+}
+
+#[test]
+fn relaxed_sites_stay_in_bounds() {
+    let bounds = Rect::from_w_h(100.0, 100.0);
+    let relaxed = carbon_tessellation::lloyd_relax(&sites(), bounds, 5);
+
+    assert_eq!(relaxed.len(), sites().len());
+    assert!(relaxed.iter().all(|p| bounds.contains(*p)));
+}
@@ -0,0 +1,116 @@
+use nannou::geom::Rect;
+use roughr::core::{FillStyle, Options};
+use roughr::Srgba;
+use nn_001_web_test::sketch_edition::{parse_seeds, run_report, ReportFormat};
+use nn_001_web_test::sketch_model::{ItemStyle, LayoutItem, Model, Shapes};
+use nn_001_web_test::sketch_traits::{collect_traits, histograms, histograms_to_csv, histograms_to_markdown};
+
+fn item(shape: Shapes, cell_count: u32, fill_style: Option<FillStyle>) -> LayoutItem {
+    LayoutItem {
+        cell_count,
+        style: Some(ItemStyle {
+            fill_colour: Srgba::new(0.0, 0.0, 0.0, 1.0),
+            stroke_colour: None,
+            fill_style,
+            seed: 0,
+            options: Options::default(),
+        }),
+        ..LayoutItem::new(shape, Rect::from_w_h(10.0, 10.0))
+    }
+}
+
+#[test]
+fn traits_count_shapes_fills_and_blocks() {
+    let settings = Model::new().settings;
+    let palette = [
+        "#de323f".to_string(),
+        "#f6f2f2".to_string(),
+        "#9eccce".to_string(),
+        "#4481a6".to_string(),
+        "#182c49".to_string(),
+    ];
+    let layout = vec![
+        vec![
+            item(Shapes::Square, 3, Some(FillStyle::Dots)),
+            item(Shapes::Star { points: 5 }, 1, Some(FillStyle::Hachure)),
+        ],
+        vec![
+            item(Shapes::Star { points: 7 }, 1, Some(FillStyle::Dots)),
+            item(Shapes::Empty, 2, None),
+        ],
+    ];
+
+    let traits = collect_traits(&settings, &palette, &layout);
+
+    assert_eq!(traits.layout, "grid");
+    assert_eq!(traits.palette_index, Some(1));
+    assert_eq!(traits.item_count, 3);
+    assert_eq!(traits.shape_counts["star"], 2);
+    assert_eq!(traits.shape_counts["square"], 1);
+    assert!(!traits.shape_counts.contains_key("empty"));
+    assert_eq!(traits.dominant_fill_style, "Dots");
+    assert_eq!(traits.merged_blocks, 1);
+
+    let other = ["#000".to_string(), "#111".to_string(), "#222".to_string(), "#333".to_string(), "#444".to_string()];
+    assert_eq!(collect_traits(&settings, &other, &layout).palette_index, None);
+}
+
+#[test]
+fn histograms_count_seeds_per_value() {
+    let settings = Model::new().settings;
+    let palette: [String; 5] = Default::default();
+
+    let small = vec![vec![item(Shapes::Circle, 1, None)]];
+    let merged: Vec<Vec<LayoutItem>> = vec![(0..10).map(|_| item(Shapes::Square, 2, None)).collect()];
+    let pieces = vec![
+        collect_traits(&settings, &palette, &small),
+        collect_traits(&settings, &palette, &merged),
+        collect_traits(&settings, &palette, &merged),
+        collect_traits(&settings, &palette, &small),
+    ];
+
+    let h = histograms(&pieces);
+    assert_eq!(h["layout"]["grid"], 4);
+    assert_eq!(h["palette_index"]["other"], 4);
+    assert_eq!(h["merged_blocks"]["10"], 2);
+    assert_eq!(h["has_shape"]["circle"], 2);
+    assert_eq!(h["dominant_fill_style"]["none"], 4);
+
+    let csv = histograms_to_csv(&h, pieces.len());
+    assert!(csv.starts_with("trait,value,count,percent\n"));
+    assert!(csv.contains("has_shape,square,2,50.0\n"));
+    // numbers sort as numbers
+    let zero = csv.find("merged_blocks,0,").unwrap();
+    let ten = csv.find("merged_blocks,10,").unwrap();
+    assert!(zero < ten);
+
+    let md = histograms_to_markdown(&h, pieces.len());
+    assert!(md.contains("## layout"));
+    assert!(md.contains("| grid | 4 | 100.0 |"));
+}
+
+#[test]
+fn report_writes_traits_and_histograms() {
+    let out_dir = std::env::temp_dir().join(format!("nn_001_report_{}", std::process::id()));
+    let _ = std::fs::remove_dir_all(&out_dir);
+    let out_dir = out_dir.to_string_lossy().to_string();
+
+    let mut settings = Model::new().settings;
+    settings.row_total = 5;
+    settings.col_total = 4;
+
+    let seeds = parse_seeds("1..=3").unwrap();
+    let traits = run_report(&settings, &seeds, ReportFormat::Both, &out_dir, "t").unwrap();
+    assert_eq!(traits.len(), 3);
+    assert_eq!(traits[2].seed_text, "3");
+
+    let json: serde_json::Value = serde_json::from_str(
+        &std::fs::read_to_string(format!("{}/t_003_3.traits.json", out_dir)).unwrap()
+    ).unwrap();
+    assert_eq!(json["seed_text"], "3");
+    assert_eq!(json["item_count"], traits[2].item_count);
+
+    let csv = std::fs::read_to_string(format!("{}/t_traits.csv", out_dir)).unwrap();
+    assert!(csv.contains("layout,grid,3,100.0"));
+    assert!(std::path::Path::new(&format!("{}/t_traits.md", out_dir)).exists());
+}